@@ -0,0 +1,50 @@
+use arboard::{Clipboard, ImageData};
+use macroquad::texture::Image;
+use std::cell::RefCell;
+
+thread_local! {
+    // Kept open for the whole session: on Linux the app has to stay the clipboard's
+    // owner for copied text to remain pasteable elsewhere
+    static CLIPBOARD: RefCell<Option<Clipboard>> = const { RefCell::new(None) };
+}
+
+/// Run an operation on the system clipboard, opening it on first use
+fn with_clipboard<T>(f: impl FnOnce(&mut Clipboard) -> Result<T, arboard::Error>) -> Result<T, String> {
+    CLIPBOARD.with(|cell| {
+        let mut slot = cell.borrow_mut();
+        if slot.is_none() {
+            *slot = Some(Clipboard::new().map_err(|e| format!("clipboard unavailable: {}", e))?);
+        }
+        let clipboard = slot.as_mut().expect("clipboard was just opened");
+        f(clipboard).map_err(|e| e.to_string())
+    })
+}
+
+/// Put text on the system clipboard
+pub fn write_clipboard(text: &str) -> Result<(), String> {
+    with_clipboard(|clipboard| clipboard.set_text(text))
+}
+
+/// Put an RGBA image on the system clipboard
+pub fn write_clipboard_image(image: &Image) -> Result<(), String> {
+    let data = ImageData {
+        width: image.width as usize,
+        height: image.height as usize,
+        bytes: image.bytes.as_slice().into(),
+    };
+    with_clipboard(|clipboard| clipboard.set_image(data))
+}
+
+/// Get the text on the system clipboard, if it holds any
+pub fn read_clipboard() -> Option<String> {
+    let text = with_clipboard(|clipboard| match clipboard.get_text() {
+        Ok(text) => Ok(Some(text)),
+        // An empty clipboard, or one holding only an image, isn't worth reporting
+        Err(arboard::Error::ContentNotAvailable) => Ok(None),
+        Err(e) => Err(e),
+    });
+    text.unwrap_or_else(|e| {
+        eprintln!("Failed to read clipboard: {}", e);
+        None
+    })
+}
@@ -0,0 +1,103 @@
+use crate::theme::ThemeColors;
+use crate::{AngleMode, ExportStyle, Frame, NodeColorMode, NumberScheme, SegmentColorMode, SnapGrid};
+use directories::ProjectDirs;
+use macroquad::color::Color;
+use serde::Deserialize;
+use std::collections::BTreeMap;
+use std::path::PathBuf;
+
+// Name of the config file inside the platform config directory
+const CONFIG_FILE: &str = "config.toml";
+// Name of the config file looked for in the current directory
+const LOCAL_CONFIG_FILE: &str = "sigil.toml";
+
+/// Settings read from the config file. Anything left out keeps its built-in default.
+#[derive(Debug, Default, Deserialize)]
+pub struct Config {
+    pub animation_speed: Option<f32>, // Speed of the sigil drawing animation
+    pub frame: Option<Frame>,         // Circle, or a polygon with a given number of sides
+    pub ring_width: Option<f32>,      // Thickness of the main circle
+    pub line_width: Option<f32>,      // Thickness of the sigil's lines
+    pub emphasize_power: Option<bool>, // Highlight nodes sharing the power number
+    pub ghost_vowels: Option<bool>,   // Show vowels as faded decorative points
+    pub collapse_repeats: Option<bool>, // Merge consecutive points with the same number
+    pub keep_vowels: Option<bool>,    // Keep vowels in the intention instead of eliminating them
+    pub curved: Option<bool>,         // Curve the path smoothly through the points
+    pub close_loop: Option<bool>,     // Finish the path with a line back to the first point
+    pub nodes_behind_lines: Option<bool>, // Draw points underneath the path
+    pub save_message_duration: Option<f32>, // Seconds the save message stays up
+    pub font: Option<String>,         // Path to a TTF font used for all text
+    pub min_segment_length: Option<f32>, // Merge away segments shorter than this many pixels
+    pub node_colors: Option<NodeColorMode>, // How nodes are colored
+    pub breathe: Option<bool>,        // Un-draw the sigil after animating it, and loop
+    pub angle_mode: Option<AngleMode>, // How the points' angles are chosen
+    pub number_scheme: Option<NumberScheme>, // How letters are turned into numbers
+    pub max_intention_len: Option<usize>, // Longest intention in single-line input mode
+    pub radial_mode: Option<bool>,    // Place each point nearer the center the smaller its number
+    pub preserve_order: Option<bool>, // Trace the letters in the order they're written instead of shuffling them
+    pub pop_in_nodes: Option<bool>,   // Show nodes only as the animation reaches them
+    pub segment_colors: Option<SegmentColorMode>, // How path segments are colored
+    pub size_by_value: Option<bool>,  // Size each node by its number
+    pub show_stats: Option<bool>,     // Show the local usage counts on the start screen
+    pub snap_grid: Option<SnapGrid>,  // Grid dragged nodes snap to while Shift is held
+    pub export_size: Option<u16>,     // Width and height of saved images in pixels
+    pub export_transparent: Option<bool>, // Leave the background of saved PNGs and SVGs transparent
+    pub export_style: Option<ExportStyle>, // Colors saved sigils are drawn in
+    pub srgb_export: Option<bool>,    // Gamma-encode exported colors from linear to sRGB
+    pub save_json: Option<bool>,      // Write a JSON description next to each saved image
+    pub sketchy: Option<bool>,        // Draw lines with a hand-drawn wobble
+    pub spokes: Option<bool>,         // Draw faint lines from the center to each node
+    pub drop_shadow: Option<bool>,    // Draw a shadow under the sigil
+    pub glow: Option<bool>,           // Draw a soft glow around the lines
+    pub shadow_offset: Option<f32>,   // Shadow offset in pixels
+    pub shadow_color: Option<String>, // Shadow color as "#RRGGBB" or "#RRGGBBAA"
+    pub theme: Option<String>,        // Name of the color palette to start with
+    pub invert: Option<bool>,         // Draw every color as its negative
+    pub high_dpi: Option<bool>,       // Render at the display's full resolution on scaled displays
+    #[serde(default)]
+    pub themes: BTreeMap<String, ThemeColors>, // Extra named color palettes
+}
+
+impl Config {
+    /// Get the locations searched for a config file, in order:
+    /// the platform config directory (`~/.config/sigil-gen/config.toml` on Linux,
+    /// `%APPDATA%\sigil-gen\config\config.toml` on Windows), then `sigil.toml`
+    /// in the current directory
+    pub fn search_paths() -> Vec<PathBuf> {
+        let mut paths = Vec::new();
+        if let Some(dirs) = ProjectDirs::from("", "", "sigil-gen") {
+            paths.push(dirs.config_dir().join(CONFIG_FILE));
+        }
+        paths.push(PathBuf::from(LOCAL_CONFIG_FILE));
+        paths
+    }
+
+    /// Load the first config file found, falling back to defaults if there is none
+    /// or it can't be read
+    pub fn load() -> Self {
+        let Some(path) = Self::search_paths().into_iter().find(|p| p.is_file()) else {
+            return Self::default();
+        };
+        let parsed = std::fs::read_to_string(&path)
+            .map_err(|e| e.to_string())
+            .and_then(|text| toml::from_str(&text).map_err(|e| e.to_string()));
+        match parsed {
+            Ok(config) => config,
+            Err(e) => {
+                eprintln!("Failed to load config from {}: {}", path.display(), e);
+                Self::default()
+            }
+        }
+    }
+}
+
+/// Parse a color written as "#RRGGBB" or "#RRGGBBAA"
+pub fn parse_color(text: &str) -> Result<Color, String> {
+    let hex = text.trim().strip_prefix('#').unwrap_or(text.trim());
+    if !(hex.len() == 6 || hex.len() == 8) || !hex.chars().all(|c| c.is_ascii_hexdigit()) {
+        return Err(format!("\"{}\" is not a color (expected #RRGGBB or #RRGGBBAA)", text));
+    }
+    let channel = |i: usize| u8::from_str_radix(&hex[i..i + 2], 16).unwrap_or(0);
+    let alpha = if hex.len() == 8 { channel(6) } else { 255 };
+    Ok(Color::from_rgba(channel(0), channel(2), channel(4), alpha))
+}
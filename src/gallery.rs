@@ -0,0 +1,84 @@
+use macroquad::texture::Image;
+use std::path::{Path, PathBuf};
+
+/// Find the PNGs saved in a directory, newest first, keeping at most `limit` of them
+pub fn saved_images(dir: &Path, limit: usize) -> Vec<PathBuf> {
+    let Ok(entries) = std::fs::read_dir(dir) else {
+        return Vec::new();
+    };
+    let mut images: Vec<(PathBuf, Option<std::time::SystemTime>)> = entries
+        .filter_map(|entry| entry.ok())
+        .map(|entry| entry.path())
+        .filter(|path| path.extension().is_some_and(|ext| ext == "png"))
+        .map(|path| {
+            let modified = path.metadata().and_then(|m| m.modified()).ok();
+            (path, modified)
+        })
+        .collect();
+    images.sort_by_key(|(_, modified)| std::cmp::Reverse(*modified));
+    images.into_iter().take(limit).map(|(path, _)| path).collect()
+}
+
+/// Read a PNG file into an RGBA image, whatever color type it was saved with
+pub fn read_png(path: &Path) -> Result<Image, String> {
+    let file = std::fs::File::open(path).map_err(|e| e.to_string())?;
+    let mut decoder = png::Decoder::new(std::io::BufReader::new(file));
+    decoder.set_transformations(png::Transformations::normalize_to_color8());
+    let mut reader = decoder.read_info().map_err(|e| e.to_string())?;
+    let mut buffer = vec![0; reader.output_buffer_size()];
+    let info = reader.next_frame(&mut buffer).map_err(|e| e.to_string())?;
+    let (width, height) = (info.width, info.height);
+    if width > u16::MAX as u32 || height > u16::MAX as u32 {
+        return Err(format!("{}x{} is too large to show", width, height));
+    }
+    let pixels = &buffer[..info.buffer_size()];
+    let bytes: Vec<u8> = match info.color_type {
+        png::ColorType::Rgba => pixels.to_vec(),
+        png::ColorType::Rgb => pixels.chunks(3).flat_map(|p| [p[0], p[1], p[2], 255]).collect(),
+        png::ColorType::GrayscaleAlpha => pixels.chunks(2).flat_map(|p| [p[0], p[0], p[0], p[1]]).collect(),
+        png::ColorType::Grayscale => pixels.iter().flat_map(|&v| [v, v, v, 255]).collect(),
+        png::ColorType::Indexed => return Err("unexpanded palette image".to_string()),
+    };
+    Ok(Image {
+        width: width as u16,
+        height: height as u16,
+        bytes,
+    })
+}
+
+/// Shrink an image to fit within `size` pixels on its longer side, averaging the
+/// block of pixels behind each thumbnail pixel. Smaller images are returned as they are
+pub fn thumbnail(image: &Image, size: u16) -> Image {
+    let (width, height) = (image.width as usize, image.height as usize);
+    let longest = width.max(height);
+    if longest <= size as usize {
+        return image.clone();
+    }
+    let thumb_width = (width * size as usize / longest).max(1);
+    let thumb_height = (height * size as usize / longest).max(1);
+    let mut bytes = Vec::with_capacity(thumb_width * thumb_height * 4);
+    for ty in 0..thumb_height {
+        let y0 = ty * height / thumb_height;
+        let y1 = ((ty + 1) * height / thumb_height).max(y0 + 1);
+        for tx in 0..thumb_width {
+            let x0 = tx * width / thumb_width;
+            let x1 = ((tx + 1) * width / thumb_width).max(x0 + 1);
+            let mut sum = [0u32; 4];
+            for y in y0..y1 {
+                for x in x0..x1 {
+                    let i = (y * width + x) * 4;
+                    for (total, &channel) in sum.iter_mut().zip(&image.bytes[i..i + 4]) {
+                        *total += channel as u32;
+                    }
+                }
+            }
+            let count = ((y1 - y0) * (x1 - x0)) as u32;
+            bytes.extend(sum.iter().map(|total| (total / count) as u8));
+        }
+    }
+    Image {
+        width: thumb_width as u16,
+        height: thumb_height as u16,
+        bytes,
+    }
+}
@@ -1,7 +1,28 @@
 use macroquad::prelude::*;
-use std::collections::HashSet;
+use std::collections::{HashMap, HashSet};
 use std::f32::consts::PI;
-use std::path::Path;
+use std::path::{Path, PathBuf};
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+mod clipboard;
+mod config;
+mod gallery;
+mod record;
+mod rng;
+mod share_code;
+mod stats;
+mod svg;
+mod theme;
+mod transliterate;
+
+use clipboard::{read_clipboard, write_clipboard, write_clipboard_image};
+use config::{parse_color, Config};
+use record::{RecordPoint, SigilRecord, RECORD_VERSION};
+use rng::SeededRng;
+use share_code::SigilCode;
+use stats::UsageStats;
+use theme::Theme;
+use transliterate::fold_to_ascii;
 
 // TODO: Figure out why it doesn't wanna work on Windows Proper (in QEMU) but it works under WINE
 // TODO: This code could probably be somewhat refactored
@@ -9,8 +30,195 @@ use std::path::Path;
 
 
 // Constants for the sigil's appearance and animation
+const WINDOW_SIZE: i32 = 800; // Width and height of the window when it first opens
 const CIRCLE_RADIUS: f32 = 250.0; // Radius of the main circle
-const ANIMATION_SPEED: f32 = 3.0; // Speed of the sigil drawing animation
+const MIN_POLYGON_SIDES: u8 = 3; // Fewest sides a polygon frame can have
+const MAX_POLYGON_SIDES: u8 = 12; // Most sides a polygon frame can have
+const OUTPUT_DIR: &str = "sigils"; // Directory sigils are saved to by default
+const FILENAME_INTENTION_LEN: usize = 32; // Most characters of the intention put in a saved file's name
+const EXPORT_SIZE: u16 = 600; // Default width and height of a saved image, which the layout is drawn at
+const EXPORT_SIZE_PRESETS: [u16; 3] = [600, 1200, 2400]; // Export sizes cycled through on the sigil screen
+const MIN_EXPORT_SIZE: u16 = 64; // Smallest export size allowed in the config
+const MAX_EXPORT_SIZE: u16 = 8192; // Largest export size allowed in the config
+const MAX_GIF_SIZE: u16 = 600; // Largest size an animated GIF is saved at, to keep encoding quick
+const GIF_FRAMES_PER_LINE: usize = 8; // Animated GIF frames spent drawing each line
+const GIF_HOLD_DELAY: u16 = 200; // Hundredths of a second the finished sigil shows before the GIF loops
+const EXPORT_VARIANT_SIZES: [u16; 4] = [256, 512, 1024, 2048]; // Sizes written when saving all sizes at once
+const MAX_VARIANTS: usize = 100; // Most variants of one intention saved by a single --variants run
+const ANIMATION_SPEED: f32 = 3.0; // Default speed of the sigil drawing animation
+const MIN_ANIMATION_SPEED: f32 = 0.5; // Slowest allowed animation speed
+const MAX_ANIMATION_SPEED: f32 = 10.0; // Fastest allowed animation speed
+const ANIMATION_SPEED_STEP: f32 = 1.25; // Factor applied per mouse wheel notch or +/- press
+const MIN_ZOOM: f32 = 0.5; // Smallest the sigil can be zoomed out to on screen
+const MAX_ZOOM: f32 = 3.0; // Largest the sigil can be zoomed in to on screen
+const ZOOM_STEP: f32 = 1.25; // Factor applied per mouse wheel notch or Ctrl+/- press when zooming
+const NODE_RADIUS: f32 = 10.0; // Radius of the sigil's nodes
+const MIN_ANGLE_SEPARATION: f32 = 0.1; // Closest two jittered points may sit around the circle, in radians
+const SPACING_PASSES: usize = 50; // Most rounds of nudging used to spread jittered points apart
+const POWER_NODE_GROWTH: f32 = 4.0; // Extra radius of nodes emphasized as the power number
+const MIN_VALUE_NODE_RADIUS: f32 = 5.0; // Radius of a 0 node when sizing nodes by value
+const MAX_VALUE_NODE_RADIUS: f32 = 16.0; // Radius of a 9 node when sizing nodes by value
+// Every control, grouped by the screen it works on, for the help screen
+const HELP_SECTIONS: [(&str, &[(&str, &str)]); 6] = [
+    ("Anywhere", &[
+        ("H or F1", "Help (F1 when typing)"),
+        ("F3", "Debug overlay"),
+        ("F5", "Freeze the seed"),
+        ("F12", "Screenshot"),
+    ]),
+    ("Start screen", &[
+        ("SPACE", "Begin"),
+        ("O", "Open last saved sigil"),
+        ("S", "Settings"),
+        ("G", "Gallery"),
+        ("T", "Theme"),
+        ("DEL", "Clear usage stats"),
+        ("ESC", "Quit"),
+    ]),
+    ("Input screen", &[
+        ("ENTER", "Make the sigil"),
+        ("CTRL+ENTER", "Make it (multi-line)"),
+        ("TAB", "Text or salt"),
+        ("F6", "Multi-line input"),
+        ("F7", "Keep vowels"),
+        ("F8", "Letter numbering"),
+        ("ARROWS", "Move cursor"),
+        ("HOME/END", "Start/end of text"),
+        ("SHIFT+move", "Select"),
+        ("CTRL+ARROWS", "Move by word"),
+        ("CLICK", "Place cursor"),
+        ("DRAG", "Select"),
+        ("DOUBLE-CLICK", "Select word"),
+        ("CTRL+A", "Select all"),
+        ("CTRL+C/X/V", "Copy, cut, paste"),
+        ("CTRL+Z/Y", "Undo, redo"),
+        ("ESC", "Back to start"),
+    ]),
+    ("Sigil screen", &[
+        ("SPACE", "Animate"),
+        ("S", "Save as"),
+        ("SHIFT+S", "Save all sizes"),
+        ("M", "Animated SVG"),
+        ("SHIFT+M", "Animated GIF"),
+        ("G", "Regenerate"),
+        ("SHIFT+G", "Gallery"),
+        ("A", "Re-angle"),
+        ("SHIFT+A", "Angle mode"),
+        ("R", "Start over"),
+        ("ESC", "Edit the intention"),
+        ("L", "Lock layout"),
+        ("F", "Focus lock"),
+        ("E", "Note"),
+        ("K", "Copy share code"),
+        ("C", "Copy image"),
+        ("Q (hold)", "Previous sigil"),
+        ("+/-", "Animation speed"),
+        ("WHEEL", "Zoom"),
+        ("CTRL +/-/0", "Zoom in, out, reset"),
+        ("DRAG", "Move node"),
+        ("SHIFT+DRAG", "Snap node"),
+        ("B", "Breathe"),
+        ("SHIFT+B", "Transparent export"),
+        ("P", "Power number"),
+        ("V", "Ghost vowels"),
+        ("N", "Node order"),
+        ("J", "Close loop"),
+        ("U", "Curves"),
+        ("D", "Shadow"),
+        ("SHIFT+D", "Glow"),
+        ("W", "Sketchy"),
+        ("O", "Spokes"),
+        ("TAB", "Node colors"),
+        ("Y", "Line colors"),
+        ("Z", "Size by value"),
+        ("T", "Theme"),
+        ("I", "Invert"),
+        ("X", "Export size"),
+        ("[ ]", "Ring width"),
+    ]),
+    ("Animation", &[
+        ("SPACE", "Pause or resume"),
+        ("LEFT/RIGHT", "Step one line"),
+        ("+/-, WHEEL", "Speed"),
+        ("ESC", "Stop"),
+    ]),
+    ("Gallery", &[
+        ("ARROWS", "Select"),
+        ("ENTER", "View"),
+        ("O", "Open the sigil"),
+        ("ESC", "Back"),
+    ]),
+];
+
+// Digits 0-9 as 3x5 pixel glyphs for exported images, one row per byte with the
+// leftmost pixel in the highest of the three bits
+const DIGIT_GLYPHS: [[u8; 5]; 10] = [
+    [0b111, 0b101, 0b101, 0b101, 0b111],
+    [0b010, 0b110, 0b010, 0b010, 0b111],
+    [0b111, 0b001, 0b111, 0b100, 0b111],
+    [0b111, 0b001, 0b111, 0b001, 0b111],
+    [0b101, 0b101, 0b111, 0b001, 0b001],
+    [0b111, 0b100, 0b111, 0b001, 0b111],
+    [0b111, 0b100, 0b111, 0b101, 0b111],
+    [0b111, 0b001, 0b001, 0b001, 0b001],
+    [0b111, 0b101, 0b111, 0b101, 0b111],
+    [0b111, 0b101, 0b111, 0b001, 0b111],
+];
+const RING_WIDTH: f32 = 3.0; // Default thickness of the main circle
+const MIN_RING_WIDTH: f32 = 1.0; // Thinnest allowed main circle
+const MAX_RING_WIDTH: f32 = 12.0; // Thickest allowed main circle
+const LINE_WIDTH: f32 = 3.0; // Default thickness of the sigil's lines
+const MIN_LINE_WIDTH: f32 = 1.0; // Thinnest allowed sigil lines
+const MAX_LINE_WIDTH: f32 = 12.0; // Thickest allowed sigil lines
+const GLOW_PASSES: usize = 4; // Widening translucent strokes drawn under each line for the glow
+const GLOW_SPREAD: f32 = 4.0; // Extra width each glow stroke adds, in pixels
+const GLOW_ALPHA: f32 = 0.12; // Opacity of each glow stroke
+const SHADOW_OFFSET: f32 = 6.0; // Default drop shadow offset in pixels
+const SHADOW_COLOR: Color = Color::new(0.0, 0.0, 0.0, 0.6); // Default drop shadow color
+const BACKGROUND_COLOR: Color = Color::new(10.0 / 255.0, 5.0 / 255.0, 20.0 / 255.0, 1.0); // Default background of the window and export
+const GRID_SPACING: f32 = 25.0; // Distance between grid lines (or rings) that dragged nodes snap to
+const POLAR_ANGLE_STEP: f32 = PI / 12.0; // Angle between the spokes of the polar snap grid
+const GRID_COLOR: Color = Color::new(0.5, 0.6, 0.9, 0.25); // Snap grid shown while dragging
+const FOCUS_UNLOCK_HOLD: f32 = 2.0; // Seconds Escape must be held to leave focus lock
+const NODE_POP_IN: f32 = 0.2; // Fraction of its incoming line over which a node pops in
+const SALT_MAX_DIGITS: usize = 9; // Longest numeric salt that can be typed
+const SKETCH_STEP: f32 = 18.0; // Distance between wobble points along a sketchy line
+const CURVE_SUBDIVISIONS: usize = 16; // Straight pieces each segment of a curved path is drawn with
+const SKETCH_AMPLITUDE: f32 = 2.5; // Furthest a sketchy line strays from the straight path
+const SAVE_MESSAGE_DURATION: f32 = 1.0; // Default seconds the save message stays up
+const SAVE_ERROR_DURATION: f32 = 4.0; // Seconds a failed save's message stays up, long enough to read the error
+const SAVE_MESSAGE_FADE: f32 = 0.3; // Seconds the save message takes to fade out at the end
+const STATUS_MESSAGE_DURATION: f32 = 2.0; // Seconds a status message stays on screen
+const UI_REFERENCE_WIDTH: f32 = 800.0; // Window width text is laid out for at its normal size
+const MIN_UI_SCALE: f32 = 0.75; // Smallest text is scaled to in narrow windows
+const MAX_UI_SCALE: f32 = 2.0; // Largest text is scaled to in wide windows
+const UI_MARGIN: f32 = 20.0; // Space kept between text and the sides of the window
+const SETTINGS_VISIBLE_ROWS: usize = 10; // Settings listed at once, scrolling to keep the selection visible
+const GALLERY_LIMIT: usize = 48; // Most saved sigils shown in the gallery, newest first
+const GALLERY_COLUMNS: usize = 4; // Thumbnails per gallery row
+const THUMBNAIL_SIZE: u16 = 120; // Longest side of a gallery thumbnail in pixels
+const GALLERY_SPACING: f32 = 16.0; // Gap between gallery thumbnails
+const GALLERY_NOTE_HEIGHT: f32 = 16.0; // Room under each gallery thumbnail for its note
+const MINIMIZED_FRAME_DELAY: Duration = Duration::from_millis(100); // Wait between frames while minimized
+const NOTE_MAX_LEN: usize = 120; // Longest note that can be attached to a sigil
+const INTENTION_MAX_LEN: usize = 100; // Default longest intention in single-line input mode
+const MULTILINE_MAX_LEN: usize = 500; // Longest intention in multi-line input mode, and the most the single-line limit can be raised to
+const INPUT_WIDTH: f32 = 400.0; // Width of the input box that multi-line text wraps to
+const INPUT_LINE_HEIGHT: f32 = 24.0; // Vertical distance between lines of multi-line input
+const DOUBLE_CLICK_TIME: f64 = 0.4; // Most seconds between two clicks on the same spot that make a double-click
+const UNDO_LIMIT: usize = 100; // Most edits of the intention that can be undone
+const GHOST_RADIUS: f32 = 4.0; // Radius of the decorative vowel points
+const GHOST_RING: f32 = 0.85; // Fraction of the circle radius where vowel points sit
+const GHOST_COLOR: Color = Color::new(0.8, 0.8, 0.9, 0.35); // Translucent vowel point color
+const SPOKE_COLOR: Color = Color::new(0.7, 0.7, 0.8, 0.18); // Faint construction lines from the center to each node
+
+/// A saved sigil listed in the gallery
+struct GalleryEntry {
+    path: PathBuf,           // The saved PNG
+    record: Option<PathBuf>, // The JSON saved next to it, if any, for re-rendering it live
+    note: String,            // Note saved in that JSON, empty if there's none
+    thumbnail: Texture2D,    // Shrunken copy of the image
+}
 
 /// Represents a point in the sigil, with a relative position and a number label
 #[derive(Clone)]
@@ -19,43 +227,713 @@ struct SigilPoint {
     relative_pos: Vec2,
     // The number associated with this point (0-9)
     number: u8,
+    // The intention character the number came from
+    source: char,
 }
 
 /// Enum for the different states of the application
 #[derive(Clone)]
 enum State {
+    Loading,    // Assets are being loaded
     Start,      // Initial screen
     Input,      // User is entering their intention
     Display,    // Sigil is displayed
-    Animating { progress: f32, line: usize }, // Sigil is being animated
+    Animating { progress: f32, line: usize, reverse: bool, paused: bool }, // Sigil is being animated (or un-drawn when reversing)
     Saving,     // Sigil is being saved
+    Note { original: String }, // User is writing a note for the sigil (original kept for cancel)
+    ChooseFormat, // User is picking the file format to save in
+    ConfirmReset, // User is asked whether to discard the sigil and start over
+    Exporting { base: PathBuf, done: usize }, // Sigil is being saved at every size, one per frame (`done` written so far)
+    Settings { selected: usize }, // User is adjusting generation options (index into `Setting::ALL`)
+    Gallery { selected: usize, viewing: bool }, // User is browsing saved sigils (index into `gallery`, full-screen when viewing)
+    Help { previous: Box<State> }, // User is reading the controls (the state to go back to kept for closing)
+}
+
+/// The shape enclosing the sigil
+#[derive(Clone, Copy, PartialEq, Debug, serde::Deserialize)]
+#[serde(rename_all = "snake_case")]
+enum Frame {
+    Circle,      // A perfect circle
+    Polygon(u8), // A regular polygon with this many sides and a corner at the top
+}
+
+impl Frame {
+    /// Get the next frame in the cycle: the circle, then polygons with more and more sides
+    fn next(self) -> Self {
+        match self {
+            Frame::Circle => Frame::Polygon(MIN_POLYGON_SIDES),
+            Frame::Polygon(sides) if sides < MAX_POLYGON_SIDES => Frame::Polygon(sides + 1),
+            Frame::Polygon(_) => Frame::Circle,
+        }
+    }
+
+    /// Get the distance from the center to the frame's edge in the direction of `angle`
+    fn radius_at(self, angle: f32) -> f32 {
+        match self {
+            Frame::Circle => CIRCLE_RADIUS,
+            Frame::Polygon(sides) => {
+                let sector = 2.0 * PI / sides as f32;
+                let apothem = CIRCLE_RADIUS * (sector / 2.0).cos();
+                let offset = (angle + PI / 2.0).rem_euclid(sector) - sector / 2.0;
+                apothem / offset.cos()
+            }
+        }
+    }
+
+    /// Get the polygon's corners relative to the center, or nothing for a circle
+    fn vertices(self) -> Vec<Vec2> {
+        match self {
+            Frame::Circle => Vec::new(),
+            Frame::Polygon(sides) => (0..sides)
+                .map(|i| Vec2::from_angle(i as f32 / sides as f32 * 2.0 * PI - PI / 2.0) * CIRCLE_RADIUS)
+                .collect(),
+        }
+    }
+}
+
+/// The grid dragged nodes snap to while Shift is held
+#[derive(Clone, Copy, PartialEq, Debug, serde::Deserialize)]
+#[serde(rename_all = "snake_case")]
+enum SnapGrid {
+    Cartesian, // Square grid centered on the circle
+    Polar,     // Rings around the center crossed by evenly spaced spokes
+}
+
+impl SnapGrid {
+    /// Get the next grid in the cycle
+    fn next(self) -> Self {
+        match self {
+            SnapGrid::Cartesian => SnapGrid::Polar,
+            SnapGrid::Polar => SnapGrid::Cartesian,
+        }
+    }
+}
+
+/// A generation option shown on the settings screen
+#[derive(Clone, Copy, PartialEq, Debug)]
+enum Setting {
+    Frame,
+    AngleMode,
+    RadialMode,
+    PreserveOrder,
+    KeepVowels,
+    NumberScheme,
+    GhostVowels,
+    CollapseRepeats,
+    EmphasizePower,
+    NodeColors,
+    SegmentColors,
+    SizeByValue,
+    CloseLoop,
+    Curved,
+    NodesBehindLines,
+    DropShadow,
+    Glow,
+    Sketchy,
+    Spokes,
+    Theme,
+    Invert,
+    Breathe,
+    PopInNodes,
+    SnapGrid,
+    ExportSize,
+    ExportStyle,
+    TransparentExport,
+    SrgbExport,
+    SaveJson,
+    ShowStats,
+}
+
+impl Setting {
+    // Every setting, in the order they're listed on screen
+    const ALL: [Setting; 30] = [
+        Setting::Frame,
+        Setting::AngleMode,
+        Setting::RadialMode,
+        Setting::PreserveOrder,
+        Setting::KeepVowels,
+        Setting::NumberScheme,
+        Setting::GhostVowels,
+        Setting::CollapseRepeats,
+        Setting::EmphasizePower,
+        Setting::NodeColors,
+        Setting::SegmentColors,
+        Setting::SizeByValue,
+        Setting::CloseLoop,
+        Setting::Curved,
+        Setting::NodesBehindLines,
+        Setting::DropShadow,
+        Setting::Glow,
+        Setting::Sketchy,
+        Setting::Spokes,
+        Setting::Theme,
+        Setting::Invert,
+        Setting::Breathe,
+        Setting::PopInNodes,
+        Setting::SnapGrid,
+        Setting::ExportSize,
+        Setting::ExportStyle,
+        Setting::TransparentExport,
+        Setting::SrgbExport,
+        Setting::SaveJson,
+        Setting::ShowStats,
+    ];
+
+    /// Get the name shown for this setting
+    fn label(self) -> &'static str {
+        match self {
+            Setting::Frame => "Frame",
+            Setting::AngleMode => "Point layout",
+            Setting::RadialMode => "Distance by number",
+            Setting::PreserveOrder => "Keep letter order",
+            Setting::KeepVowels => "Keep vowels",
+            Setting::NumberScheme => "Letter numbers",
+            Setting::GhostVowels => "Show vowels as ghost points",
+            Setting::CollapseRepeats => "Merge repeated numbers",
+            Setting::EmphasizePower => "Emphasize power number",
+            Setting::NodeColors => "Node colors",
+            Setting::SegmentColors => "Line colors",
+            Setting::SizeByValue => "Size nodes by value",
+            Setting::CloseLoop => "Close the loop",
+            Setting::Curved => "Curved lines",
+            Setting::NodesBehindLines => "Nodes behind lines",
+            Setting::DropShadow => "Drop shadow",
+            Setting::Glow => "Glowing lines",
+            Setting::Sketchy => "Hand-drawn lines",
+            Setting::Spokes => "Lines from center to nodes",
+            Setting::Theme => "Color theme",
+            Setting::Invert => "Inverted colors",
+            Setting::Breathe => "Breathe after animating",
+            Setting::PopInNodes => "Nodes appear with their line",
+            Setting::SnapGrid => "Snap grid for dragging",
+            Setting::ExportSize => "Export size",
+            Setting::ExportStyle => "Export colors",
+            Setting::TransparentExport => "Transparent background on export",
+            Setting::SrgbExport => "sRGB gamma on export",
+            Setting::SaveJson => "Save JSON alongside",
+            Setting::ShowStats => "Show usage stats",
+        }
+    }
+}
+
+/// File formats a sigil can be saved in
+#[derive(Clone, Copy, PartialEq, Debug)]
+enum ExportFormat {
+    Png,  // Lossless image with metadata, plus a JSON sidecar
+    Svg,  // Vector image that animates its own drawing
+    Jpeg, // Lossy image
+    Gif,  // Single-frame palette image
+}
+
+/// Colors a saved sigil is drawn in
+#[derive(Clone, Copy, PartialEq, Debug, serde::Deserialize)]
+#[serde(rename_all = "snake_case")]
+enum ExportStyle {
+    Color,                  // The palette shown on screen
+    MonoBlackOnWhite,       // Black line art on white, for printing and stencils
+    MonoWhiteOnTransparent, // White line art on a transparent background, for overlays
+}
+
+impl ExportStyle {
+    /// Get the next style in the cycle
+    fn next(self) -> Self {
+        match self {
+            ExportStyle::Color => ExportStyle::MonoBlackOnWhite,
+            ExportStyle::MonoBlackOnWhite => ExportStyle::MonoWhiteOnTransparent,
+            ExportStyle::MonoWhiteOnTransparent => ExportStyle::Color,
+        }
+    }
+}
+
+/// How the sigil's nodes are colored
+#[derive(Clone, Copy, PartialEq, Debug, serde::Deserialize)]
+#[serde(rename_all = "snake_case")]
+enum NodeColorMode {
+    Classic,   // Green start, red end, orange in between
+    Gradient,  // Blend from green to red along the path
+    Single,    // Every node the same color
+    Intention, // Hues derived from the intention text and each node's number
+}
+
+impl NodeColorMode {
+    /// Get the next mode in the cycle
+    fn next(self) -> Self {
+        match self {
+            NodeColorMode::Classic => NodeColorMode::Gradient,
+            NodeColorMode::Gradient => NodeColorMode::Single,
+            NodeColorMode::Single => NodeColorMode::Intention,
+            NodeColorMode::Intention => NodeColorMode::Classic,
+        }
+    }
+}
+
+/// How the sigil's path segments are colored, based on the two letters each one connects
+#[derive(Clone, Copy, PartialEq, Debug, serde::Deserialize)]
+#[serde(rename_all = "snake_case")]
+enum SegmentColorMode {
+    Uniform,      // Every segment the same color
+    AlphabetHalf, // Both letters in a-m (or digits 0-4), both in n-z (or 5-9), or one of each
+    Parity,       // Both numbers even, both odd, or one of each
+}
+
+impl SegmentColorMode {
+    /// Get the next mode in the cycle
+    fn next(self) -> Self {
+        match self {
+            SegmentColorMode::Uniform => SegmentColorMode::AlphabetHalf,
+            SegmentColorMode::AlphabetHalf => SegmentColorMode::Parity,
+            SegmentColorMode::Parity => SegmentColorMode::Uniform,
+        }
+    }
+}
+
+/// How the points' angles around the circle are chosen
+#[derive(Clone, Copy, PartialEq, Debug, serde::Deserialize)]
+#[serde(rename_all = "snake_case")]
+enum AngleMode {
+    Jittered,   // Evenly spaced with a little randomness, then shuffled
+    Even,       // Evenly spaced and shuffled, without randomness
+    FromNumber, // Each number always points the same way (number / 10 of a full turn)
+}
+
+impl AngleMode {
+    /// Get the next mode in the cycle
+    fn next(self) -> Self {
+        match self {
+            AngleMode::Jittered => AngleMode::Even,
+            AngleMode::Even => AngleMode::FromNumber,
+            AngleMode::FromNumber => AngleMode::Jittered,
+        }
+    }
+}
+
+/// How the letters of the intention are turned into numbers
+#[derive(Clone, Copy, PartialEq, Debug, serde::Deserialize)]
+#[serde(rename_all = "snake_case")]
+enum NumberScheme {
+    SimpleMod10, // Position in the alphabet from a = 0, wrapping every ten letters
+    Pythagorean, // Position in the alphabet from a = 1, wrapping every nine letters (1-9, no zeros)
+    Chaldean,    // The traditional Chaldean values (1-8), which follow sound rather than position
+}
+
+impl NumberScheme {
+    /// Get the next scheme in the cycle
+    fn next(self) -> Self {
+        match self {
+            NumberScheme::SimpleMod10 => NumberScheme::Pythagorean,
+            NumberScheme::Pythagorean => NumberScheme::Chaldean,
+            NumberScheme::Chaldean => NumberScheme::SimpleMod10,
+        }
+    }
+
+    /// Get the number a lowercase ASCII letter or digit stands for. Digits stand for themselves
+    fn number_for(self, c: char) -> u8 {
+        // Chaldean values for a to z
+        const CHALDEAN: [u8; 26] = [1, 2, 3, 4, 5, 8, 3, 5, 1, 1, 2, 3, 4, 5, 7, 8, 1, 2, 3, 4, 6, 6, 6, 5, 1, 7];
+        if c.is_ascii_digit() {
+            return c as u8 - b'0';
+        }
+        let index = c as u8 - b'a';
+        match self {
+            NumberScheme::SimpleMod10 => index % 10,
+            NumberScheme::Pythagorean => index % 9 + 1,
+            NumberScheme::Chaldean => CHALDEAN[index as usize],
+        }
+    }
 }
 
 /// Main application struct holding all state
 struct SigilApp {
     state: State,                // Current state of the app
     intention: String,           // User's intention text
+    numbers: Vec<u8>,            // Shuffled number sequence the points are placed from
+    sources: Vec<char>,          // Intention characters matching each entry of `numbers`
     points: Vec<SigilPoint>,     // Points that make up the sigil
+    ghost_points: Vec<SigilPoint>, // Decorative vowel points (not part of the path)
     blink_timer: f32,            // Timer for blinking cursor
     save_timer: f32,             // Timer for save message
-    cursor_pos: usize,           // Cursor position in the input string
+    last_save_result: Option<Result<String, String>>, // Path written by the last save, or why it failed
+    cursor_pos: usize,           // Cursor position in the input string, as a byte index on a character boundary
     selection_start: Option<usize>, // Start of text selection (if any)
+    undo_stack: Vec<(String, usize)>, // Intention and cursor before each edit, newest last
+    redo_stack: Vec<(String, usize)>, // Undone edits that can be reapplied, newest last
+    typing_run: Option<usize>,   // Cursor position after the last typed character, while a run of typing continues
+    last_click: Option<(f64, usize)>, // Time and text position of the last click in the input box, to spot double-clicks
+    drag_selecting: bool,        // Whether the mouse button pressed in the input box is still held, selecting text
+    max_intention_len: usize,    // Longest intention in single-line input mode
+    input_scroll: usize,         // First character shown in the single-line input box, scrolled to keep the cursor in view
+    emphasize_power: bool,       // Whether to highlight nodes sharing the most frequent number
+    seed: u64,                   // Seed used to shuffle the current number sequence
+    layout_seed: u64,            // Seed used to place the current points around the circle
+    animation_speed: f32,        // Current speed of the sigil drawing animation
+    zoom: f32,                   // On-screen scale of the sigil around the center (saved images aren't affected)
+    ghost_vowels: bool,          // Whether to show vowels as faded decorative points
+    output_dir: PathBuf,         // Directory timestamped files are saved to
+    output_path: Option<PathBuf>, // Explicit file to save to instead of a timestamped one
+    force_overwrite: bool,       // Whether saving may replace an existing explicit output file
+    ring_width: f32,             // Thickness of the main circle
+    line_width: f32,             // Thickness of the sigil's lines, on screen and in exports
+    locked: bool,                // Whether the layout is locked against reset/regeneration
+    nodes_behind_lines: bool,    // Whether points are drawn underneath the path
+    font: Font,                  // Font used for all text
+    min_segment_length: f32,     // Segments shorter than this (in pixels) are merged away
+    drop_shadow: bool,           // Whether to draw a shadow under the sigil
+    glow: bool,                  // Whether the lines glow, with soft wider strokes under them
+    shadow_offset: f32,          // How far the shadow is offset down and right
+    shadow_color: Color,         // Color of the shadow
+    debug_overlay: bool,         // Whether to annotate nodes with their index and position
+    node_color_mode: NodeColorMode, // How nodes are colored
+    size_by_value: bool,         // Whether each node's size reflects its number
+    segment_color_mode: SegmentColorMode, // How path segments are colored
+    breathe: bool,               // Whether the animation un-draws itself and loops
+    frozen_seed: Option<u64>,    // Seed reused for every generation this session, if frozen
+    note: String,                // Free-text note saved with the sigil
+    multiline: bool,             // Whether Enter inserts a newline (Ctrl+Enter submits)
+    angle_mode: AngleMode,       // How the points' angles are chosen
+    radial_mode: bool,           // Whether each point's distance from the center follows its number
+    preserve_order: bool,        // Whether the path visits the letters in the order they're written instead of shuffled
+    sketchy: bool,               // Whether lines are drawn with a hand-drawn wobble
+    salt: u32,                   // Personal number added (mod 10) to every number in the sequence
+    salt_focused: bool,          // Whether typing goes to the salt field instead of the intention
+    output_dir_existed: bool,    // Whether the output directory was already there at startup
+    pop_in_nodes: bool,          // Whether nodes appear only as the animation reaches them
+    srgb_export: bool,           // Whether exported colors are treated as linear and gamma-encoded to sRGB
+    save_json: bool,             // Whether saved images get a JSON file describing the sigil next to them
+    collapse_repeats: bool,      // Whether consecutive points with the same number are merged into one
+    keep_vowels: bool,           // Whether vowels stay in the intention instead of being eliminated
+    number_scheme: NumberScheme, // How letters are turned into numbers
+    export_size: u16,            // Width and height of saved images, in pixels
+    export_transparent: bool,    // Whether saved PNGs and SVGs leave the background transparent
+    export_style: ExportStyle,   // Colors saved sigils are drawn in
+    close_loop: bool,            // Whether the path ends with a line back to its first point
+    curved: bool,                // Whether the path curves smoothly through the points
+    focus_locked: bool,          // Whether all input is ignored until Escape is held
+    quitting: bool,              // Whether Escape on the start screen asked to close the app
+    unlock_hold: f32,            // How long Escape has been held while focus locked
+    dragging: Option<usize>,     // Index of the node being dragged by hand, if any
+    snap_grid: SnapGrid,         // Grid dragged nodes snap to while Shift is held
+    stats: UsageStats,           // Local counts of sigils generated and saved
+    show_stats: bool,            // Whether the counts are shown on the start screen
+    frame: Frame,                // Shape enclosing the sigil
+    prev_points: Option<Vec<SigilPoint>>, // Points of the sigil generated before this one
+    showing_previous: bool,      // Whether `points` and `prev_points` are swapped for comparison
+    save_message_duration: f32,  // Seconds the save message stays up, including its fade
+    status_message: String,      // Short feedback shown on the sigil screen (e.g. after copying)
+    status_timer: f32,           // Seconds left before the status message disappears
+    gallery: Vec<GalleryEntry>,  // Saved sigils loaded for the gallery, newest first
+    gallery_image: Option<Texture2D>, // Full-size image of the gallery entry being viewed
+    spokes: bool,                // Whether faint lines join the center to each node
+    themes: Vec<(String, Theme)>, // Available color palettes by name, the built-in one first
+    theme_index: usize,          // Index of the active palette in `themes`
+    invert: bool,                // Whether every color is drawn as its negative
 }
 
 impl SigilApp {
     /// Create a new SigilApp with default state
     fn new() -> Self {
         Self {
-            state: State::Start,
+            state: State::Loading,
             intention: String::new(),
+            numbers: Vec::new(),
+            sources: Vec::new(),
             points: Vec::new(),
+            ghost_points: Vec::new(),
             blink_timer: 0.0,
             save_timer: 0.0,
+            last_save_result: None,
             cursor_pos: 0,
             selection_start: None,
+            undo_stack: Vec::new(),
+            redo_stack: Vec::new(),
+            typing_run: None,
+            last_click: None,
+            drag_selecting: false,
+            max_intention_len: INTENTION_MAX_LEN,
+            input_scroll: 0,
+            emphasize_power: false,
+            seed: 0,
+            layout_seed: 0,
+            animation_speed: ANIMATION_SPEED,
+            zoom: 1.0,
+            ghost_vowels: false,
+            output_dir: PathBuf::from(OUTPUT_DIR),
+            output_path: None,
+            force_overwrite: false,
+            ring_width: RING_WIDTH,
+            line_width: LINE_WIDTH,
+            locked: false,
+            nodes_behind_lines: false,
+            font: Font::default(),
+            min_segment_length: 0.0,
+            drop_shadow: false,
+            glow: false,
+            shadow_offset: SHADOW_OFFSET,
+            shadow_color: SHADOW_COLOR,
+            debug_overlay: false,
+            node_color_mode: NodeColorMode::Classic,
+            size_by_value: false,
+            segment_color_mode: SegmentColorMode::Uniform,
+            breathe: false,
+            frozen_seed: None,
+            note: String::new(),
+            multiline: false,
+            angle_mode: AngleMode::Jittered,
+            radial_mode: false,
+            preserve_order: false,
+            sketchy: false,
+            salt: 0,
+            salt_focused: false,
+            output_dir_existed: false,
+            pop_in_nodes: false,
+            srgb_export: false,
+            save_json: true,
+            collapse_repeats: false,
+            keep_vowels: false,
+            number_scheme: NumberScheme::SimpleMod10,
+            export_size: EXPORT_SIZE,
+            export_transparent: false,
+            export_style: ExportStyle::Color,
+            close_loop: false,
+            curved: false,
+            focus_locked: false,
+            quitting: false,
+            unlock_hold: 0.0,
+            dragging: None,
+            snap_grid: SnapGrid::Cartesian,
+            stats: UsageStats::default(),
+            show_stats: true,
+            frame: Frame::Circle,
+            prev_points: None,
+            showing_previous: false,
+            save_message_duration: SAVE_MESSAGE_DURATION,
+            status_message: String::new(),
+            status_timer: 0.0,
+            gallery: Vec::new(),
+            gallery_image: None,
+            spokes: false,
+            themes: theme::presets(),
+            theme_index: 0,
+            invert: false,
+        }
+    }
+
+    /// Apply any settings given in the config file
+    fn apply_config(&mut self, config: &Config) {
+        if let Some(speed) = config.animation_speed {
+            self.animation_speed = speed.clamp(MIN_ANIMATION_SPEED, MAX_ANIMATION_SPEED);
+        }
+        if let Some(width) = config.ring_width {
+            self.ring_width = width.clamp(MIN_RING_WIDTH, MAX_RING_WIDTH);
+        }
+        if let Some(width) = config.line_width {
+            self.line_width = width.clamp(MIN_LINE_WIDTH, MAX_LINE_WIDTH);
+        }
+        if let Some(emphasize) = config.emphasize_power {
+            self.emphasize_power = emphasize;
+        }
+        if let Some(ghost) = config.ghost_vowels {
+            self.ghost_vowels = ghost;
+        }
+        if let Some(behind) = config.nodes_behind_lines {
+            self.nodes_behind_lines = behind;
+        }
+        if let Some(min_len) = config.min_segment_length {
+            self.min_segment_length = min_len.max(0.0);
+        }
+        if let Some(mode) = config.node_colors {
+            self.node_color_mode = mode;
+        }
+        if let Some(breathe) = config.breathe {
+            self.breathe = breathe;
+        }
+        if let Some(mode) = config.angle_mode {
+            self.angle_mode = mode;
+        }
+        if let Some(len) = config.max_intention_len {
+            self.max_intention_len = len.clamp(1, MULTILINE_MAX_LEN);
+        }
+        if let Some(radial) = config.radial_mode {
+            self.radial_mode = radial;
+        }
+        if let Some(preserve) = config.preserve_order {
+            self.preserve_order = preserve;
+        }
+        if let Some(mode) = config.segment_colors {
+            self.segment_color_mode = mode;
+        }
+        if let Some(size) = config.size_by_value {
+            self.size_by_value = size;
+        }
+        if let Some(pop_in) = config.pop_in_nodes {
+            self.pop_in_nodes = pop_in;
+        }
+        if let Some(collapse) = config.collapse_repeats {
+            self.collapse_repeats = collapse;
+        }
+        if let Some(size) = config.export_size {
+            self.export_size = size.clamp(MIN_EXPORT_SIZE, MAX_EXPORT_SIZE);
+        }
+        if let Some(curved) = config.curved {
+            self.curved = curved;
+        }
+        if let Some(close) = config.close_loop {
+            self.close_loop = close;
+        }
+        if let Some(transparent) = config.export_transparent {
+            self.export_transparent = transparent;
+        }
+        if let Some(style) = config.export_style {
+            self.export_style = style;
+        }
+        if let Some(keep) = config.keep_vowels {
+            self.keep_vowels = keep;
+        }
+        if let Some(scheme) = config.number_scheme {
+            self.number_scheme = scheme;
+        }
+        if let Some(spokes) = config.spokes {
+            self.spokes = spokes;
+        }
+        if let Some(duration) = config.save_message_duration {
+            self.save_message_duration = duration.max(0.0);
+        }
+        if let Some(frame) = config.frame {
+            self.frame = match frame {
+                Frame::Polygon(sides) => Frame::Polygon(sides.clamp(MIN_POLYGON_SIDES, MAX_POLYGON_SIDES)),
+                circle => circle,
+            };
+        }
+        if let Some(show) = config.show_stats {
+            self.show_stats = show;
+        }
+        if let Some(grid) = config.snap_grid {
+            self.snap_grid = grid;
+        }
+        if let Some(save_json) = config.save_json {
+            self.save_json = save_json;
+        }
+        if let Some(srgb) = config.srgb_export {
+            self.srgb_export = srgb;
+        }
+        if let Some(sketchy) = config.sketchy {
+            self.sketchy = sketchy;
+        }
+        if let Some(shadow) = config.drop_shadow {
+            self.drop_shadow = shadow;
+        }
+        if let Some(glow) = config.glow {
+            self.glow = glow;
+        }
+        if let Some(offset) = config.shadow_offset {
+            self.shadow_offset = offset;
+        }
+        if let Some(color) = &config.shadow_color {
+            match parse_color(color) {
+                Ok(color) => self.shadow_color = color,
+                Err(e) => eprintln!("Invalid shadow_color in config: {}", e),
+            }
+        }
+        for (name, colors) in &config.themes {
+            match colors.resolve() {
+                Ok(theme) => match self.themes.iter_mut().find(|(existing, _)| existing == name) {
+                    Some(existing) => existing.1 = theme,
+                    None => self.themes.push((name.clone(), theme)),
+                },
+                Err(e) => eprintln!("Invalid theme \"{}\" in config: {}", name, e),
+            }
+        }
+        if let Some(invert) = config.invert {
+            self.invert = invert;
+        }
+        if let Some(name) = &config.theme {
+            if let Err(e) = self.select_theme(name) {
+                eprintln!("Invalid theme in config: {}", e);
+            }
+        }
+    }
+
+    /// Apply the options given on the command line, which win over the config file's
+    fn apply_args(&mut self, args: &[String]) {
+        // A directory given with --out wins over SIGIL_OUT, and a file is saved to exactly
+        if let Some(dir) = std::env::var_os("SIGIL_OUT").filter(|dir| !dir.is_empty()) {
+            self.output_dir = PathBuf::from(dir);
+        }
+        match arg_value(args, "--out").map(PathBuf::from) {
+            Some(dir) if is_dir_path(&dir) => self.output_dir = dir,
+            path => self.output_path = path,
+        }
+        self.force_overwrite = args.iter().any(|arg| arg == "--force");
+        self.output_dir_existed = self.save_dir().exists();
+        if let Some(name) = arg_value(args, "--theme") {
+            if let Err(e) = self.select_theme(&name) {
+                eprintln!("Invalid --theme: {}", e);
+            }
+        }
+        // Use the same seed for every sigil this session if one was given
+        if let Some(seed) = arg_value(args, "--seed") {
+            match seed.parse() {
+                Ok(seed) => self.frozen_seed = Some(seed),
+                Err(e) => eprintln!("Invalid --seed {}: {}", seed, e),
+            }
+        }
+        if let Some(len) = arg_value(args, "--max-length") {
+            match len.parse::<usize>() {
+                Ok(len) => self.max_intention_len = len.clamp(1, MULTILINE_MAX_LEN),
+                Err(e) => eprintln!("Invalid --max-length {}: {}", len, e),
+            }
+        }
+        if let Some(size) = arg_value(args, "--size") {
+            match size.parse::<u16>() {
+                Ok(size) => self.export_size = size.clamp(MIN_EXPORT_SIZE, MAX_EXPORT_SIZE),
+                Err(e) => eprintln!("Invalid --size {}: {}", size, e),
+            }
+        }
+    }
+
+    /// Get the active color palette
+    fn theme(&self) -> Theme {
+        self.themes[self.theme_index].1
+    }
+
+    /// Get the color actually drawn for `color`: its negative when inverted, otherwise unchanged
+    fn ink(&self, color: Color) -> Color {
+        if self.invert {
+            Color::new(1.0 - color.r, 1.0 - color.g, 1.0 - color.b, color.a)
+        } else {
+            color
+        }
+    }
+
+    /// Make the palette with the given name active
+    fn select_theme(&mut self, name: &str) -> Result<(), String> {
+        match self.themes.iter().position(|(existing, _)| existing == name) {
+            Some(index) => {
+                self.theme_index = index;
+                Ok(())
+            }
+            None => {
+                let names: Vec<&str> = self.themes.iter().map(|(name, _)| name.as_str()).collect();
+                Err(format!("no theme named \"{}\" (available: {})", name, names.join(", ")))
+            }
         }
     }
 
+    /// Switch to the next palette, wrapping back to the first
+    fn cycle_theme(&mut self) {
+        self.theme_index = (self.theme_index + 1) % self.themes.len();
+    }
+
+    /// Load the fonts and other assets named in the config, then move on to the start screen
+    async fn load_assets(&mut self, config: &Config) {
+        if let Some(path) = &config.font {
+            match load_ttf_font(path).await {
+                Ok(font) => self.font = font,
+                Err(e) => eprintln!("Failed to load font {}: {}", path, e),
+            }
+        }
+        self.state = State::Start;
+    }
+
     /// Get the center of the screen as a Vec2
     fn get_center(&self) -> Vec2 {
         vec2(screen_width() / 2.0, screen_height() / 2.0)
@@ -66,590 +944,3592 @@ impl SigilApp {
         self.get_center() + point.relative_pos
     }
 
-    /// Generate the sigil points from the user's intention
+    /// Generate a new sigil from the user's intention with a fresh random seed,
+    /// or the frozen session seed if there is one
     fn generate_sigil(&mut self) {
-        if self.intention.trim().is_empty() {
-            return;
+        self.seed = self.frozen_seed.unwrap_or_else(fresh_seed);
+        self.layout_seed = self.seed;
+        self.generate_sigil_with_seed();
+        if !self.points.is_empty() {
+            self.stats.generated += 1;
+            self.stats.save();
         }
+    }
 
-        // Remove vowels and duplicate characters from the intention
-        let vowels = "aeiouAEIOU";
-        let mut seen = HashSet::new();
-        let filtered: String = self.intention
-            .chars()
-            .filter(|c| c.is_ascii_alphanumeric() && !vowels.contains(*c))
-            .map(|c| c.to_ascii_lowercase())
-            .filter(|c| seen.insert(*c))
-            .collect();
+    /// Count a successful save in the usage stats
+    fn record_save(&mut self) {
+        self.stats.saved += 1;
+        self.stats.save();
+    }
 
-        if filtered.is_empty() {
+    /// Generate the sigil points from the user's intention using the current seeds
+    fn generate_sigil_with_seed(&mut self) {
+        if self.intention.trim().is_empty() {
             return;
         }
+        let mut rng = SeededRng::new(self.seed);
 
-        // Convert filtered characters to numbers (0-9)
-        let mut numbers: Vec<u8> = filtered
-            .chars()
-            .map(|c| if c.is_ascii_digit() {
-                c as u8 - b'0'
-            } else {
-                (c as u8 - b'a') % 10
-            })
-            .collect();
+        // Convert filtered characters to numbers (0-9), shifted by the salt
+        let salt = (self.salt % 10) as u8;
+        let (mut sources, mut numbers): (Vec<char>, Vec<u8>) =
+            intention_to_numbers(&self.intention, self.keep_vowels, self.number_scheme)
+                .into_iter()
+                .map(|(c, n)| (c, (n + salt) % 10))
+                .unzip();
+        if numbers.is_empty() {
+            return;
+        }
 
-        // Shuffle the numbers using Fisher-Yates
-        for i in (1..numbers.len()).rev() {
-            let j = rand::gen_range(0, i + 1);
-            numbers.swap(i, j);
+        // Shuffle the numbers (and the characters they came from) using Fisher-Yates,
+        // unless the path should follow the letters in the order they're written
+        if !self.preserve_order {
+            for i in (1..numbers.len()).rev() {
+                let j = rng.index(0, i + 1);
+                numbers.swap(i, j);
+                sources.swap(i, j);
+            }
+        }
+        if self.collapse_repeats {
+            collapse_repeated_numbers(&mut numbers, &mut sources);
         }
+        self.numbers = numbers;
+        self.sources = sources;
 
-        // Generate random angles for each point
-        let mut angles: Vec<f32> = (0..numbers.len())
-            .map(|i| (i as f32 / numbers.len() as f32) * 2.0 * PI)
-            .collect();
+        self.place_points();
+        self.state = State::Display;
+    }
 
-        // Add randomness to the angles
-        for angle in &mut angles {
-            *angle += rand::gen_range(-0.2, 0.2);
-        }
+    /// Get the characters of the intention that become numbers
+    fn filtered_letters(&self) -> String {
+        filter_letters(&self.intention, self.keep_vowels)
+    }
 
-        // Shuffle the angles
-        for i in (1..angles.len()).rev() {
-            let j = rand::gen_range(0, i + 1);
-            angles.swap(i, j);
+    /// Place the current number sequence around the circle using the layout seed.
+    /// The jitter and the shuffle both come from that seed, so a given sequence and
+    /// layout seed always land in exactly the same spots
+    fn place_points(&mut self) {
+        let mut rng = SeededRng::new(self.layout_seed);
+        // Keep the outgoing sigil around so the two can be compared
+        if !self.points.is_empty() {
+            self.prev_points = Some(std::mem::take(&mut self.points));
         }
 
+        // Generate an angle for each point
+        let count = self.numbers.len();
+        let angles: Vec<f32> = if self.angle_mode == AngleMode::FromNumber {
+            self.numbers.iter().map(|&num| num as f32 / 10.0 * 2.0 * PI).collect()
+        } else {
+            // Letters kept in order go clockwise from the top, like reading around a dial
+            let start = if self.preserve_order { -PI / 2.0 } else { 0.0 };
+            let mut angles: Vec<f32> = (0..count)
+                .map(|i| start + (i as f32 / count as f32) * 2.0 * PI)
+                .collect();
+
+            // Add randomness to the angles
+            if self.angle_mode == AngleMode::Jittered {
+                for angle in &mut angles {
+                    *angle += rng.range(-0.2, 0.2);
+                }
+                enforce_min_spacing(&mut angles, MIN_ANGLE_SEPARATION);
+            }
+
+            // Shuffle the angles, unless the points go around in order
+            if !self.preserve_order {
+                for i in (1..angles.len()).rev() {
+                    let j = rng.index(0, i + 1);
+                    angles.swap(i, j);
+                }
+            }
+            angles
+        };
+
         // Create the sigil points from the numbers and angles
-        self.points = numbers
-            .into_iter()
+        self.points = self.numbers
+            .iter()
+            .zip(&self.sources)
             .zip(angles)
-            .map(|(num, angle)| {
+            .map(|((&num, &source), angle)| {
+                // In radial mode 0 sits closest to the center and 9 on the frame
+                let distance = if self.radial_mode { 0.4 + num as f32 / 9.0 * 0.6 } else { 1.0 };
                 SigilPoint {
-                    relative_pos: vec2(angle.cos(), angle.sin()) * CIRCLE_RADIUS,
+                    relative_pos: vec2(angle.cos(), angle.sin()) * self.frame.radius_at(angle) * distance,
                     number: num,
+                    source,
                 }
             })
             .collect();
+        // Only the placed points are merged; the full sequence stays for the next re-layout
+        merge_short_segments(&mut self.points, self.min_segment_length);
 
-        self.state = State::Display;
+        self.generate_ghost_points();
     }
 
-    /// Save the current sigil as a PNG file
-    fn save_sigil(&self) -> std::io::Result<()> {
-        use macroquad::texture::Image;
-        // Create output directory if it doesn't exist
-        let dir = "sigils";
-        if !Path::new(dir).exists() {
-            std::fs::create_dir(dir)?;
+    /// Re-randomize where the points sit while keeping the number sequence and its order
+    fn regenerate_angles(&mut self) {
+        if self.numbers.is_empty() {
+            return;
         }
+        self.layout_seed = fresh_seed();
+        self.place_points();
+    }
 
-        // Generate a filename with timestamp and sanitized intention
-        let timestamp = chrono::Local::now().format("%Y%m%d_%H%M%S");
-        let sanitized_intention = self.intention
-            .chars()
-            .filter(|c| c.is_ascii_alphanumeric())
-            .collect::<String>();
-        let filename = format!("{}/sigil_{}_{}.png", dir, timestamp, sanitized_intention);
-
-        // PNG dimensions and center
-        let img_size = 600u16;
-        let img_center = img_size as f32 / 2.0;
-        let mut image = Image::gen_image_color(img_size, img_size, Color::from_rgba(10, 5, 20, 255));
-
-        // Helper closure to convert relative to image coordinates
-        let transform_point = |relative_pos: Vec2| -> (u32, u32) {
-            let x = (img_center + relative_pos.x).round().clamp(0.0, (img_size - 1) as f32) as u32;
-            let y = (img_center + relative_pos.y).round().clamp(0.0, (img_size - 1) as f32) as u32;
-            (x, y)
-        };
-
-        // Draw the main circle (using Bresenham's algorithm for a circle)
-        let r = CIRCLE_RADIUS.round() as i32;
-        let cx = img_center.round() as i32;
-        let cy = img_center.round() as i32;
-        for t in 0..360 {
-            let theta = (t as f32).to_radians();
-            let x = (cx as f32 + r as f32 * theta.cos()).round() as i32;
-            let y = (cy as f32 + r as f32 * theta.sin()).round() as i32;
-            if x >= 0 && x < img_size as i32 && y >= 0 && y < img_size as i32 {
-                image.set_pixel(x as u32, y as u32, GRAY);
-            }
+    /// Generate the decorative vowel points, placing each vowel at the angle matching
+    /// its position in the intention so the full word structure stays visible
+    fn generate_ghost_points(&mut self) {
+        self.ghost_points.clear();
+        // Kept vowels are already part of the path
+        if !self.ghost_vowels || self.keep_vowels {
+            return;
         }
-
-        // Draw the sigil lines
-        if self.points.len() > 1 {
-            for i in 0..self.points.len() - 1 {
-                let (x0, y0) = transform_point(self.points[i].relative_pos);
-                let (x1, y1) = transform_point(self.points[i + 1].relative_pos);
-                draw_line_on_image(&mut image, x0, y0, x1, y1, SKYBLUE);
+        let chars: Vec<char> = self.intention.chars().filter_map(fold_to_ascii).collect();
+        for (i, c) in chars.iter().enumerate() {
+            if "aeiou".contains(*c) {
+                let angle = (i as f32 / chars.len() as f32) * 2.0 * PI - PI / 2.0;
+                self.ghost_points.push(SigilPoint {
+                    relative_pos: vec2(angle.cos(), angle.sin()) * self.frame.radius_at(angle) * GHOST_RING,
+                    number: self.number_scheme.number_for(*c),
+                    source: *c,
+                });
             }
         }
+    }
 
-        // Draw start (green) and end (red) points
-        if !self.points.is_empty() {
-            let (start_x, start_y) = transform_point(self.points[0].relative_pos);
-            draw_circle_on_image(&mut image, start_x, start_y, 10, GREEN);
-            if self.points.len() > 1 {
-                let (end_x, end_y) = transform_point(self.points[self.points.len() - 1].relative_pos);
-                draw_circle_on_image(&mut image, end_x, end_y, 10, RED);
-            }
+    /// Get the share code describing how the current sigil was generated
+    fn sigil_code(&self) -> SigilCode {
+        SigilCode {
+            intention: self.intention.clone(),
+            seed: self.seed,
+            layout_seed: self.layout_seed,
+            emphasize_power: self.emphasize_power,
+            ghost_vowels: self.ghost_vowels,
+            angle_mode: self.angle_mode,
+            salt: (self.salt % 10) as u8,
+            collapse_repeats: self.collapse_repeats,
+            keep_vowels: self.keep_vowels,
+            radial_mode: self.radial_mode,
+            preserve_order: self.preserve_order,
+            number_scheme: self.number_scheme,
+            frame: self.frame,
+            min_segment_length: self.min_segment_length,
         }
-        // Draw intermediate points (orange) and numbers
-        for (i, point) in self.points.iter().enumerate() {
-            if i != 0 && i != self.points.len() - 1 {
-                let (x, y) = transform_point(point.relative_pos);
-                draw_circle_on_image(&mut image, x, y, 10, ORANGE);
-            }
-            // Draw the number as a single pixel (for now, as text rendering is nontrivial)
-            let (x, y) = transform_point(point.relative_pos);
-            image.set_pixel(x, y, BLACK);
+    }
+
+    /// Reconstruct a sigil from a share code and show it
+    fn apply_code(&mut self, code: SigilCode) {
+        self.intention = code.intention
+            .chars()
+            .filter(|c| is_intention_char(*c) || *c == '\n')
+            .take(MULTILINE_MAX_LEN)
+            .collect();
+        self.multiline |= self.intention.contains('\n');
+        self.cursor_pos = self.intention.len();
+        self.selection_start = None;
+        self.seed = code.seed;
+        self.layout_seed = code.layout_seed;
+        self.emphasize_power = code.emphasize_power;
+        self.ghost_vowels = code.ghost_vowels;
+        self.angle_mode = code.angle_mode;
+        self.salt = code.salt as u32;
+        self.collapse_repeats = code.collapse_repeats;
+        self.keep_vowels = code.keep_vowels;
+        self.radial_mode = code.radial_mode;
+        self.preserve_order = code.preserve_order;
+        self.number_scheme = code.number_scheme;
+        self.frame = code.frame;
+        self.min_segment_length = code.min_segment_length;
+        self.state = State::Input;
+        self.generate_sigil_with_seed();
+    }
+
+    /// Get a saveable record of the current sigil
+    fn to_record(&self) -> SigilRecord {
+        SigilRecord {
+            version: RECORD_VERSION,
+            intention: self.intention.clone(),
+            note: self.note.clone(),
+            letters: self.filtered_letters(),
+            salt: self.salt,
+            seed: self.seed,
+            layout_seed: self.layout_seed,
+            points: self.points
+                .iter()
+                .map(|p| RecordPoint { number: p.number, source: Some(p.source), x: p.relative_pos.x, y: p.relative_pos.y })
+                .collect(),
         }
-        // Save the image as PNG
-        image.export_png(&filename);
-        Ok(())
     }
 
-    /// Helper to get the (start, end) indices of the current selection, if any
-    fn selection_range(&self) -> Option<(usize, usize)> {
-        self.selection_start.map(|start| {
-            if start < self.cursor_pos {
-                (start, self.cursor_pos)
-            } else {
-                (self.cursor_pos, start)
-            }
-        })
+    /// Describe how the sigil was built as JSON: the intention, the characters left after
+    /// filtering, and each point's number and position. Reloading reads the same format
+    fn export_metadata(&self) -> String {
+        self.to_record().to_json()
     }
 
-    /// Helper to delete the current selection, if any, and return true if something was deleted
-    fn delete_selection(&mut self) -> bool {
-        if let Some((start, end)) = self.selection_range() {
-            self.intention.drain(start..end);
-            self.cursor_pos = start;
-            self.selection_start = None;
-            true
-        } else {
-            false
+    /// Write the JSON description next to a saved image, unless that's turned off
+    fn save_metadata(&self, image_path: &Path) -> std::io::Result<()> {
+        if !self.save_json {
+            return Ok(());
         }
+        std::fs::write(image_path.with_extension("json"), self.export_metadata())
     }
 
-    /// Helper to check if Ctrl is held
-    fn ctrl_down() -> bool {
-        is_key_down(KeyCode::LeftControl) || is_key_down(KeyCode::RightControl)
+    /// Restore a previously saved sigil exactly as it was and show it
+    fn apply_record(&mut self, record: SigilRecord) {
+        self.intention = record.intention
+            .chars()
+            .filter(|c| is_intention_char(*c) || *c == '\n')
+            .take(MULTILINE_MAX_LEN)
+            .collect();
+        self.multiline |= self.intention.contains('\n');
+        self.cursor_pos = self.intention.len();
+        self.selection_start = None;
+        self.note = record.note;
+        self.salt = record.salt;
+        self.seed = record.seed;
+        self.layout_seed = record.layout_seed;
+        self.points = record.points
+            .iter()
+            .map(|p| SigilPoint {
+                relative_pos: vec2(p.x, p.y),
+                number: p.number,
+                // Older files don't record the source; fall back to the digit itself
+                source: p.source.unwrap_or((b'0' + p.number) as char),
+            })
+            .collect();
+        self.numbers = self.points.iter().map(|p| p.number).collect();
+        self.sources = self.points.iter().map(|p| p.source).collect();
+        self.generate_ghost_points();
+        self.state = State::Display;
     }
 
-    /// Handle text input, cursor movement, and selection (ASCII only)
-    fn handle_text_input(&mut self) {
-        // Handle character input (ASCII alphanumeric and space only)
-        while let Some(ch) = get_char_pressed() {
-            if ch.is_ascii_alphanumeric() || ch == ' ' {
-                self.delete_selection();
-                if self.intention.len() < 100 {
-                    self.intention.insert(self.cursor_pos, ch);
-                    self.cursor_pos += 1;
-                }
-            }
+    /// Load the most recently saved sigil from the output directory
+    fn load_latest(&mut self) {
+        let dir = self.save_dir();
+        let Some(path) = record::latest_record(&dir) else {
+            eprintln!("No saved sigils found in {}", dir.display());
+            return;
+        };
+        match SigilRecord::load(&path) {
+            Ok(record) => self.apply_record(record),
+            Err(e) => eprintln!("Failed to load sigil: {}", e),
         }
+    }
 
-        // Handle backspace
-        if is_key_pressed(KeyCode::Backspace) {
-            if !self.delete_selection() && self.cursor_pos > 0 {
-                self.cursor_pos -= 1;
-                self.intention.remove(self.cursor_pos);
+    /// Load thumbnails of the saved sigils and show them in the gallery
+    fn open_gallery(&mut self) {
+        self.close_gallery();
+        let dir = self.save_dir();
+        for path in gallery::saved_images(&dir, GALLERY_LIMIT) {
+            match gallery::read_png(&path) {
+                Ok(image) => {
+                    let thumbnail = Texture2D::from_image(&gallery::thumbnail(&image, THUMBNAIL_SIZE));
+                    let record = Some(path.with_extension("json")).filter(|record| record.is_file());
+                    let note = record
+                        .as_deref()
+                        .and_then(|record| SigilRecord::load(record).ok())
+                        .map(|record| record.note)
+                        .unwrap_or_default();
+                    self.gallery.push(GalleryEntry { path, record, note, thumbnail });
+                }
+                Err(e) => eprintln!("Failed to read {}: {}", path.display(), e),
             }
         }
+        if self.gallery.is_empty() {
+            eprintln!("No saved sigils found in {}", dir.display());
+            return;
+        }
+        self.state = State::Gallery { selected: 0, viewing: false };
+    }
 
-        // Handle delete
-        if is_key_pressed(KeyCode::Delete) {
-            if !self.delete_selection() && self.cursor_pos < self.intention.len() {
-                self.intention.remove(self.cursor_pos);
-            }
+    /// Free the gallery's textures once it's left
+    fn close_gallery(&mut self) {
+        for entry in self.gallery.drain(..) {
+            entry.thumbnail.delete();
         }
+        if let Some(image) = self.gallery_image.take() {
+            image.delete();
+        }
+    }
 
-        // Handle left arrow (with/without selection)
-        if is_key_pressed(KeyCode::Left) {
-            if is_key_down(KeyCode::LeftShift) || is_key_down(KeyCode::RightShift) {
-                if self.cursor_pos > 0 {
-                    self.cursor_pos -= 1;
-                    if self.selection_start.is_none() {
-                        self.selection_start = Some(self.cursor_pos + 1);
-                    }
-                }
-            } else {
-                if self.cursor_pos > 0 {
-                    self.cursor_pos -= 1;
+    /// Show gallery entry `index` full-screen at its real size
+    fn view_gallery_image(&mut self, index: usize) {
+        let Some(entry) = self.gallery.get(index) else {
+            return;
+        };
+        match gallery::read_png(&entry.path) {
+            Ok(image) => {
+                if let Some(old) = self.gallery_image.replace(Texture2D::from_image(&image)) {
+                    old.delete();
                 }
-                self.selection_start = None;
+                self.state = State::Gallery { selected: index, viewing: true };
             }
+            Err(e) => eprintln!("Failed to read {}: {}", entry.path.display(), e),
         }
+    }
 
-        // Handle right arrow (with/without selection)
-        if is_key_pressed(KeyCode::Right) {
-            if is_key_down(KeyCode::LeftShift) || is_key_down(KeyCode::RightShift) {
-                if self.cursor_pos < self.intention.len() {
+    /// Leave the gallery, re-rendering entry `index` live from its JSON if it has one
+    fn open_gallery_record(&mut self, index: usize) {
+        let Some(path) = self.gallery.get(index).and_then(|entry| entry.record.clone()) else {
+            return;
+        };
+        match SigilRecord::load(&path) {
+            Ok(record) => {
+                self.close_gallery();
+                self.apply_record(record);
+            }
+            Err(e) => eprintln!("Failed to load sigil: {}", e),
+        }
+    }
+
+    /// Get the "power number" of the sigil: the number shared by the most points.
+    /// Returns None when no single number appears more than once.
+    fn power_number(&self) -> Option<u8> {
+        let mut counts = [0usize; 10];
+        for point in &self.points {
+            counts[point.number as usize] += 1;
+        }
+        let max = *counts.iter().max()?;
+        if max < 2 || counts.iter().filter(|&&c| c == max).count() > 1 {
+            return None;
+        }
+        counts.iter().position(|&c| c == max).map(|n| n as u8)
+    }
+
+    /// Get the number to emphasize when drawing, if emphasis is enabled
+    fn emphasized_number(&self) -> Option<u8> {
+        if self.emphasize_power {
+            self.power_number()
+        } else {
+            None
+        }
+    }
+
+    /// Get the color of the node at the given index in the path
+    fn node_color(&self, index: usize) -> Color {
+        let last = self.points.len().saturating_sub(1);
+        match self.node_color_mode {
+            NodeColorMode::Classic => {
+                if index == 0 {
+                    self.theme().start
+                } else if index == last {
+                    self.theme().end
+                } else {
+                    self.theme().node
+                }
+            }
+            NodeColorMode::Gradient => {
+                let t = if last == 0 { 0.0 } else { index as f32 / last as f32 };
+                let (start, end) = (self.theme().start, self.theme().end);
+                Color::new(
+                    start.r + (end.r - start.r) * t,
+                    start.g + (end.g - start.g) * t,
+                    start.b + (end.b - start.b) * t,
+                    1.0,
+                )
+            }
+            NodeColorMode::Single => self.theme().node,
+            NodeColorMode::Intention => {
+                // FNV-1a hash of the intention picks a base hue, each number shifts it
+                let hash = self.intention
+                    .bytes()
+                    .fold(0xcbf29ce484222325u64, |h, b| (h ^ b as u64).wrapping_mul(0x100000001b3));
+                let base = (hash % 360) as f32 / 360.0;
+                let number = self.points.get(index).map_or(0, |p| p.number);
+                macroquad::color::hsl_to_rgb((base + number as f32 / 10.0).fract(), 0.7, 0.55)
+            }
+        }
+    }
+
+    /// Get the color of the path segment running from point `index` to the next one.
+    /// Letters a-m and digits 0-4 count as the first half, n-z and 5-9 as the second.
+    /// The segment closing the loop is always the plain line color.
+    fn segment_color(&self, index: usize) -> Color {
+        let (Some(a), Some(b)) = (self.points.get(index), self.points.get(index + 1)) else {
+            return self.theme().line;
+        };
+        let (first, second) = match self.segment_color_mode {
+            SegmentColorMode::Uniform => return self.theme().line,
+            SegmentColorMode::AlphabetHalf => {
+                let first_half = |c: char| matches!(c, 'a'..='m' | '0'..='4');
+                (first_half(a.source), first_half(b.source))
+            }
+            SegmentColorMode::Parity => (a.number % 2 == 0, b.number % 2 == 0),
+        };
+        match (first, second) {
+            (true, true) => self.theme().line,
+            (false, false) => VIOLET,
+            _ => PINK,
+        }
+    }
+
+    /// Get the radius a point's node is drawn with
+    fn node_radius(&self, point: &SigilPoint, emphasized: Option<u8>) -> f32 {
+        let radius = if self.size_by_value {
+            let t = point.number as f32 / 9.0;
+            MIN_VALUE_NODE_RADIUS + (MAX_VALUE_NODE_RADIUS - MIN_VALUE_NODE_RADIUS) * t
+        } else {
+            NODE_RADIUS
+        };
+        if emphasized == Some(point.number) {
+            radius + POWER_NODE_GROWTH
+        } else {
+            radius
+        }
+    }
+
+    /// Get the path segments visible at the given animation state, each as a polyline
+    /// relative to the center. The last segment is partial while animating, cut off
+    /// partway along its curve when the path is curved.
+    fn visible_segments(&self, animation: Option<(usize, f32)>) -> Vec<Vec<Vec2>> {
+        let mut segments = Vec::new();
+        if self.points.len() < 2 {
+            return segments;
+        }
+        let subdivisions = if self.curved { CURVE_SUBDIVISIONS } else { 1 };
+        let path = self.sigil_path_points(subdivisions);
+        // Completed lines
+        let completed_lines = match animation {
+            Some((current_line, _)) => current_line,
+            None => self.segment_count(),
+        };
+        for i in 0..completed_lines.min(self.segment_count()) {
+            segments.push(path[i * subdivisions..=(i + 1) * subdivisions].to_vec());
+        }
+        // The currently animating line
+        if let Some((current_line, progress)) = animation {
+            if current_line < self.segment_count() {
+                let first = current_line * subdivisions;
+                let reached = progress.clamp(0.0, 1.0) * subdivisions as f32;
+                let whole = (reached as usize).min(subdivisions);
+                let mut line = path[first..=first + whole].to_vec();
+                if whole < subdivisions {
+                    line.push(path[first + whole].lerp(path[first + whole + 1], reached.fract()));
+                }
+                segments.push(line);
+            }
+        }
+        segments
+    }
+
+    /// Get how many segments the path has, counting the one back to the start when closed
+    fn segment_count(&self) -> usize {
+        let open = self.points.len().saturating_sub(1);
+        if self.close_loop && self.points.len() > 2 {
+            open + 1
+        } else {
+            open
+        }
+    }
+
+    /// Get the whole path through the points as a polyline relative to the center, with
+    /// each segment split into `subdivisions` pieces along a Catmull-Rom curve. A single
+    /// subdivision gives the straight path. Segment `i` runs from entry `i * subdivisions`
+    /// to entry `(i + 1) * subdivisions`.
+    fn sigil_path_points(&self, subdivisions: usize) -> Vec<Vec2> {
+        let count = self.points.len();
+        if count < 2 {
+            return self.points.iter().map(|p| p.relative_pos).collect();
+        }
+        let closed = self.segment_count() == count;
+        // Neighbors past the ends of an open path repeat the end point
+        let at = |i: isize| -> Vec2 {
+            let index = if closed {
+                i.rem_euclid(count as isize) as usize
+            } else {
+                i.clamp(0, count as isize - 1) as usize
+            };
+            self.points[index].relative_pos
+        };
+        let mut path = Vec::with_capacity(self.segment_count() * subdivisions + 1);
+        for i in 0..self.segment_count() as isize {
+            let (p0, p1, p2, p3) = (at(i - 1), at(i), at(i + 1), at(i + 2));
+            for step in 0..subdivisions {
+                let t = step as f32 / subdivisions as f32;
+                let (t2, t3) = (t * t, t * t * t);
+                path.push(
+                    0.5 * (2.0 * p1
+                        + (p2 - p0) * t
+                        + (2.0 * p0 - 5.0 * p1 + 4.0 * p2 - p3) * t2
+                        + (3.0 * p1 - p0 - 3.0 * p2 + p3) * t3),
+                );
+            }
+        }
+        path.push(at(self.segment_count() as isize));
+        path
+    }
+
+    /// Get how far node `index` has grown in (0 hidden, 1 full size). With pop-in enabled,
+    /// each node after the first scales up over the end of the line leading to it
+    fn node_scale(&self, index: usize, animation: Option<(usize, f32)>) -> f32 {
+        match animation {
+            Some((line, progress)) if self.pop_in_nodes && index > 0 => {
+                let reached = line as f32 + progress - (index - 1) as f32;
+                ((reached - (1.0 - NODE_POP_IN)) / NODE_POP_IN).clamp(0.0, 1.0)
+            }
+            _ => 1.0,
+        }
+    }
+
+    /// Get the points of the stroke drawn for segment `index` along `line`: the line
+    /// itself, or a wobbly path along it in sketchy mode. The wobble is seeded by the
+    /// layout and measured from the segment's start, so a line growing during the
+    /// animation keeps its shape
+    fn stroke_points(&self, index: usize, line: &[Vec2]) -> Vec<Vec2> {
+        let (Some(&start), Some(&end)) = (line.first(), line.last()) else {
+            return Vec::new();
+        };
+        if !self.sketchy {
+            return line.to_vec();
+        }
+        let segment_seed = self.layout_seed ^ (index as u64 + 1).wrapping_mul(0x9e3779b97f4a7c15);
+        let mut points = vec![start];
+        let mut step = 1;
+        let mut walked = 0.0;
+        for pair in line.windows(2) {
+            let length = pair[0].distance(pair[1]);
+            let direction = (pair[1] - pair[0]).normalize_or_zero();
+            while (step as f32 * SKETCH_STEP) < walked + length {
+                let wobble = hash_noise(segment_seed, step) * SKETCH_AMPLITUDE;
+                points.push(pair[0] + direction * (step as f32 * SKETCH_STEP - walked) + direction.perp() * wobble);
+                step += 1;
+            }
+            walked += length;
+        }
+        points.push(end);
+        points
+    }
+
+    /// Switch to the next export size preset, or the first one if the size came from the config
+    fn cycle_export_size(&mut self) {
+        self.export_size = match EXPORT_SIZE_PRESETS.iter().position(|&size| size == self.export_size) {
+            Some(i) => EXPORT_SIZE_PRESETS[(i + 1) % EXPORT_SIZE_PRESETS.len()],
+            None => EXPORT_SIZE_PRESETS[0],
+        };
+    }
+
+    /// Save the current sigil as a PNG file and return its path
+    fn save_sigil(&self) -> std::io::Result<String> {
+        let filename = self.output_filename()?;
+        self.export_png(&self.render_to_image(), Path::new(&filename))?;
+        Ok(filename)
+    }
+
+    /// Write a rendered sigil to a PNG at the given path, with the points alongside
+    /// as JSON so it can be reloaded
+    fn export_png(&self, image: &Image, path: &Path) -> std::io::Result<()> {
+        write_png(image, path, &self.png_metadata())?;
+        self.save_metadata(path)
+    }
+
+    /// Save the animation as a looping GIF, tracing the lines one after another the way
+    /// the animation does on screen and holding the finished sigil before starting over
+    fn save_sigil_gif(&self) -> std::io::Result<String> {
+        let path = self.output_filename_as("gif")?;
+        let size = self.export_size.min(MAX_GIF_SIZE);
+        let render = |animation| {
+            let mut image = self.render_frame(size, animation);
+            // GIF pixels are either opaque or fully transparent, so fill in the background
+            if self.transparent_export() {
+                flatten_image(&mut image, self.solid_export_background());
+            }
+            image
+        };
+        // Every frame shows part of the finished sigil, so one palette picked from it
+        // serves them all, and only has to be worked out once
+        let mut finished = render(None);
+        let palette = gif::Frame::from_rgba_speed(size, size, &mut finished.bytes, 10).palette.unwrap_or_default();
+        let file = std::io::BufWriter::new(std::fs::File::create(&path)?);
+        let mut encoder = gif::Encoder::new(file, size, size, &palette).map_err(std::io::Error::other)?;
+        encoder.set_repeat(gif::Repeat::Infinite).map_err(std::io::Error::other)?;
+        // Delays are in hundredths of a second, and viewers ignore anything under two
+        let delay = (100.0 / (self.animation_speed * GIF_FRAMES_PER_LINE as f32)).round().max(2.0) as u16;
+        let frames = self.segment_count() * GIF_FRAMES_PER_LINE;
+        let mut indices: HashMap<[u8; 3], u8> = HashMap::new();
+        for frame in 0..=frames {
+            let animation = (frame < frames).then(|| {
+                let progress = (frame % GIF_FRAMES_PER_LINE) as f32 / GIF_FRAMES_PER_LINE as f32;
+                (frame / GIF_FRAMES_PER_LINE, progress)
+            });
+            let image = if animation.is_some() { render(animation) } else { finished.clone() };
+            let pixels: Vec<u8> = image
+                .bytes
+                .chunks(4)
+                .map(|pixel| {
+                    let rgb = [pixel[0], pixel[1], pixel[2]];
+                    *indices.entry(rgb).or_insert_with(|| nearest_palette_index(&palette, rgb))
+                })
+                .collect();
+            let mut gif_frame = gif::Frame::from_indexed_pixels(size, size, pixels, None);
+            gif_frame.delay = if animation.is_some() { delay } else { GIF_HOLD_DELAY };
+            encoder.write_frame(&gif_frame).map_err(std::io::Error::other)?;
+        }
+        self.save_metadata(&path)?;
+        Ok(path.display().to_string())
+    }
+
+    /// Use a text file's contents as the intention, generate the sigil and save it as a PNG
+    fn export_from_file(&mut self, path: &Path, variants: usize) -> Result<Vec<String>, String> {
+        let text = std::fs::read_to_string(path).map_err(|e| format!("can't read {}: {}", path.display(), e))?;
+        self.export_intention(&text, variants).map_err(|e| format!("{}: {}", path.display(), e))
+    }
+
+    /// Generate `variants` sigils for an intention, each from its own seed, and save them as
+    /// PNGs, numbered and with progress printed when there's more than one. Line breaks
+    /// become spaces, and anything past the multi-line length limit is dropped
+    fn export_intention(&mut self, text: &str, variants: usize) -> Result<Vec<String>, String> {
+        self.intention = text
+            .split_whitespace()
+            .collect::<Vec<_>>()
+            .join(" ")
+            .chars()
+            .filter(|c| is_intention_char(*c))
+            .take(MULTILINE_MAX_LEN)
+            .collect();
+        let base = self.output_target().map_err(|e| format!("failed to save sigil: {}", e))?;
+        // Numbered variants never write the base name itself, so only the files written are
+        // checked, and all of them up front so a refused batch doesn't leave part of one behind
+        let paths: Vec<PathBuf> = if variants == 1 {
+            vec![base]
+        } else {
+            (1..=variants).map(|number| variant_path(&base, number)).collect()
+        };
+        for path in &paths {
+            self.check_overwrite(path).map_err(|e| e.to_string())?;
+        }
+        let mut saved = Vec::new();
+        for (number, path) in (1..=variants).zip(paths) {
+            // A frozen seed gives a reproducible set: that seed, the one after it, and so on
+            self.seed = self.frozen_seed.map_or_else(fresh_seed, |seed| seed.wrapping_add(number as u64 - 1));
+            self.layout_seed = self.seed;
+            self.generate_sigil_with_seed();
+            if self.points.is_empty() {
+                return Err("no letters or digits to make a sigil from".to_string());
+            }
+            self.stats.generated += 1;
+            self.export_png(&self.render_to_image(), &path).map_err(|e| format!("failed to save {}: {}", path.display(), e))?;
+            self.record_save();
+            saved.push(path.display().to_string());
+            // Progress goes to stderr so the saved paths on stdout stay easy to script against
+            if variants > 1 {
+                eprintln!("{}/{}", number, variants);
+            }
+        }
+        Ok(saved)
+    }
+
+    /// Save the sigil in the chosen format and return its path. Raster formats all get the JSON sidecar
+    fn save_sigil_as(&self, format: ExportFormat) -> std::io::Result<String> {
+        let path = match format {
+            ExportFormat::Png => return self.save_sigil(),
+            ExportFormat::Svg => return self.save_sigil_svg(),
+            ExportFormat::Jpeg => self.output_filename_as("jpg")?,
+            ExportFormat::Gif => self.output_filename_as("gif")?,
+        };
+        let mut image = self.render_to_image();
+        // Neither format can keep partial transparency, so fill in the background
+        if self.transparent_export() {
+            flatten_image(&mut image, self.solid_export_background());
+        }
+        if format == ExportFormat::Jpeg {
+            write_jpeg(&image, &path)?;
+        } else {
+            write_gif(&image, &path)?;
+        }
+        self.save_metadata(&path)?;
+        Ok(path.display().to_string())
+    }
+
+    /// Show how a save went: the path it was written to, or the error if it failed
+    fn finish_save(&mut self, result: std::io::Result<String>) {
+        match result {
+            Ok(path) => {
+                self.record_save();
+                self.last_save_result = Some(Ok(full_path(&path)));
+            }
+            Err(e) => {
+                eprintln!("Failed to save sigil: {}", e);
+                self.last_save_result = Some(Err(e.to_string()));
+            }
+        }
+        self.save_timer = 0.0;
+        self.state = State::Saving;
+    }
+
+    /// Seconds the message for the last save stays up, longer for an error so it can be read
+    fn save_message_time(&self) -> f32 {
+        match self.last_save_result {
+            Some(Err(_)) => self.save_message_duration.max(SAVE_ERROR_DURATION),
+            _ => self.save_message_duration,
+        }
+    }
+
+    /// Start saving the sigil at every size in `EXPORT_VARIANT_SIZES`, suffixing each
+    /// filename with its size. The images are written one per frame by `step_size_export`
+    fn start_size_export(&mut self) -> std::io::Result<()> {
+        // Only the size-suffixed files are written, so the base name itself may already exist
+        let base = self.output_target()?;
+        for size in EXPORT_VARIANT_SIZES {
+            self.check_overwrite(&size_variant_path(&base, size))?;
+        }
+        self.state = State::Exporting { base, done: 0 };
+        Ok(())
+    }
+
+    /// Write the next size of an all-sizes export, or stop early if Escape is pressed.
+    /// Sizes already written are kept either way, along with the JSON sidecar
+    fn step_size_export(&mut self) {
+        let State::Exporting { base, done } = &self.state else {
+            return;
+        };
+        let (base, mut done) = (base.clone(), *done);
+        let cancelled = is_key_pressed(KeyCode::Escape);
+        if !cancelled {
+            let path = size_variant_path(&base, EXPORT_VARIANT_SIZES[done]);
+            match write_png(&self.render_image(EXPORT_VARIANT_SIZES[done]), &path, &self.png_metadata()) {
+                Ok(()) => done += 1,
+                Err(e) => {
+                    eprintln!("Failed to save {}: {}", path.display(), e);
+                    self.last_save_result = Some(Err(format!("{}: {}", path.display(), e)));
+                    self.save_timer = 0.0;
+                    self.state = State::Saving;
+                    return;
+                }
+            }
+            if done < EXPORT_VARIANT_SIZES.len() {
+                self.state = State::Exporting { base, done };
+                return;
+            }
+        }
+        if done > 0 {
+            match self.save_metadata(&base) {
+                Ok(()) => self.record_save(),
+                Err(e) => eprintln!("Failed to save sigil record: {}", e),
+            }
+        }
+        if cancelled {
+            let total = EXPORT_VARIANT_SIZES.len();
+            self.show_status(&format!("Export cancelled after {} of {} sizes", done, total));
+            self.state = State::Display;
+        } else {
+            self.last_save_result = Some(Ok(full_path(&base.display().to_string())));
+            self.save_timer = 0.0;
+            self.state = State::Saving;
+        }
+    }
+
+    /// Get the text chunks stored in saved PNGs
+    fn png_metadata(&self) -> Vec<(&str, &str)> {
+        let mut metadata = vec![("Title", self.intention.as_str()), ("Software", "sigil-gen")];
+        if !self.note.is_empty() {
+            metadata.push(("Comment", self.note.as_str()));
+        }
+        metadata
+    }
+
+    /// Get the color to write into an exported image for a possibly translucent color.
+    /// Pixels are written without blending, so it's flattened onto the background,
+    /// unless the background is transparent and the translucency can be kept as is
+    fn export_color(&self, color: Color) -> Color {
+        if self.transparent_export() {
+            self.style_ink(color)
+        } else {
+            flatten_color(self.style_ink(color), self.export_background())
+        }
+    }
+
+    /// Get the color a saved sigil draws `color` in: the single ink of a monochrome
+    /// export style, keeping the color's transparency, or the color itself
+    fn style_ink(&self, color: Color) -> Color {
+        match self.export_style {
+            ExportStyle::Color => color,
+            ExportStyle::MonoBlackOnWhite => Color { a: color.a, ..BLACK },
+            ExportStyle::MonoWhiteOnTransparent => Color { a: color.a, ..WHITE },
+        }
+    }
+
+    /// Check whether saved sigils get a transparent background, which the
+    /// monochrome styles decide for themselves
+    fn transparent_export(&self) -> bool {
+        match self.export_style {
+            ExportStyle::Color => self.export_transparent,
+            ExportStyle::MonoBlackOnWhite => false,
+            ExportStyle::MonoWhiteOnTransparent => true,
+        }
+    }
+
+    /// Get the background a saved sigil is drawn on (before any inverting)
+    fn export_background(&self) -> Color {
+        if self.transparent_export() {
+            return Color::new(0.0, 0.0, 0.0, 0.0);
+        }
+        match self.export_style {
+            ExportStyle::Color => self.theme().background,
+            _ => WHITE,
+        }
+    }
+
+    /// Get the background filled in for formats that can't be transparent, chosen
+    /// so the sigil still shows up against it
+    fn solid_export_background(&self) -> Color {
+        match self.export_style {
+            ExportStyle::Color => self.ink(self.theme().background),
+            ExportStyle::MonoBlackOnWhite => WHITE,
+            ExportStyle::MonoWhiteOnTransparent => BLACK,
+        }
+    }
+
+    /// Render the finished sigil the way it's saved, at the export size, with every
+    /// export option applied. Saving, copying and the single-image formats all start here
+    fn render_to_image(&self) -> Image {
+        self.render_image(self.export_size)
+    }
+
+    /// Render the sigil onto a square image of the given size, scaling the
+    /// layout so the circle fills it the same way at any resolution
+    fn render_image(&self, img_size: u16) -> Image {
+        self.render_frame(img_size, None)
+    }
+
+    /// Render the sigil as it looks partway through the animation, or complete for `None`
+    fn render_frame(&self, img_size: u16, animation: Option<(usize, f32)>) -> Image {
+        let scale = img_size as f32 / EXPORT_SIZE as f32;
+        let img_center = img_size as f32 / 2.0;
+        let mut image = Image::gen_image_color(img_size, img_size, self.export_background());
+
+        // Helper closure to convert relative to image coordinates
+        let transform_point = |relative_pos: Vec2| -> (u32, u32) {
+            let x = (img_center + relative_pos.x * scale).round().clamp(0.0, (img_size - 1) as f32) as u32;
+            let y = (img_center + relative_pos.y * scale).round().clamp(0.0, (img_size - 1) as f32) as u32;
+            (x, y)
+        };
+        // Unrounded position, for drawing anti-aliased strokes
+        let to_image = |relative_pos: Vec2| vec2(img_center, img_center) + relative_pos * scale;
+
+        // Draw the frame
+        match self.frame {
+            Frame::Circle => {
+                draw_ring_on_image(&mut image, img_center, img_center, CIRCLE_RADIUS * scale, self.ring_width * scale, self.style_ink(GRAY));
+            }
+            Frame::Polygon(_) => {
+                let corners = self.frame.vertices();
+                for (i, &corner) in corners.iter().enumerate() {
+                    let next = corners[(i + 1) % corners.len()];
+                    let (start, end) = (vec2(img_center, img_center) + corner * scale, vec2(img_center, img_center) + next * scale);
+                    draw_thick_line_on_image(&mut image, start, end, self.ring_width * scale, self.style_ink(GRAY));
+                }
+            }
+        }
+
+        // Draw the decorative vowel points behind the sigil
+        for point in &self.ghost_points {
+            let (x, y) = transform_point(point.relative_pos);
+            draw_circle_on_image(&mut image, x, y, (GHOST_RADIUS * scale) as u32, self.export_color(GHOST_COLOR));
+        }
+
+        // Draw the construction lines from the center out to each node
+        if self.spokes {
+            let (cx, cy) = transform_point(Vec2::ZERO);
+            for point in &self.points {
+                let (x, y) = transform_point(point.relative_pos);
+                draw_line_on_image(&mut image, cx, cy, x, y, self.export_color(SPOKE_COLOR));
+            }
+        }
+
+        // Draw the shadow underneath everything else
+        if self.drop_shadow {
+            self.export_shadow(&mut image, &transform_point, &to_image, scale, animation);
+        }
+
+        // Draw the sigil lines and points in the chosen order
+        if self.nodes_behind_lines {
+            self.export_nodes(&mut image, &transform_point, scale, animation);
+            self.export_lines(&mut image, &to_image, scale, animation);
+        } else {
+            self.export_lines(&mut image, &to_image, scale, animation);
+            self.export_nodes(&mut image, &transform_point, scale, animation);
+        }
+        // Blending is linear, so inverting the finished image is the same as
+        // inverting every color before drawing it. Monochrome styles keep their own colors
+        if self.invert && self.export_style == ExportStyle::Color {
+            for pixel in image.bytes.chunks_mut(4) {
+                for channel in &mut pixel[..3] {
+                    *channel = 255 - *channel;
+                }
+            }
+        }
+        if self.srgb_export {
+            encode_srgb(&mut image);
+        }
+        image
+    }
+
+    /// Draw the sigil lines onto an exported image
+    fn export_lines(&self, image: &mut Image, to_image: &impl Fn(Vec2) -> Vec2, scale: f32, animation: Option<(usize, f32)>) {
+        for (i, line) in self.visible_segments(animation).into_iter().enumerate() {
+            let stroke: Vec<Vec2> = self.stroke_points(i, &line).into_iter().map(to_image).collect();
+            let color = self.style_ink(self.segment_color(i));
+            let glow_passes = if self.glow { GLOW_PASSES } else { 0 };
+            for pass in (1..=glow_passes).rev() {
+                let width = (self.line_width + pass as f32 * GLOW_SPREAD) * scale;
+                draw_smooth_polyline_on_image(image, &stroke, width, Color { a: color.a * GLOW_ALPHA, ..color });
+            }
+            draw_smooth_polyline_on_image(image, &stroke, self.line_width * scale, color);
+        }
+    }
+
+    /// Draw the sigil's drop shadow onto an exported image
+    fn export_shadow(
+        &self,
+        image: &mut Image,
+        transform_point: &impl Fn(Vec2) -> (u32, u32),
+        to_image: &impl Fn(Vec2) -> Vec2,
+        scale: f32,
+        animation: Option<(usize, f32)>,
+    ) {
+        let color = self.export_color(self.shadow_color);
+        let offset = vec2(self.shadow_offset, self.shadow_offset);
+        for (i, line) in self.visible_segments(animation).into_iter().enumerate() {
+            let stroke: Vec<Vec2> = self.stroke_points(i, &line).into_iter().map(|p| to_image(p + offset)).collect();
+            draw_smooth_polyline_on_image(image, &stroke, self.line_width * scale, color);
+        }
+        let emphasized = self.emphasized_number();
+        for (i, point) in self.points.iter().enumerate() {
+            let grow = self.node_scale(i, animation);
+            if grow <= 0.0 {
+                continue;
+            }
+            let (x, y) = transform_point(point.relative_pos + offset);
+            draw_circle_on_image(image, x, y, (self.node_radius(point, emphasized) * grow * scale) as u32, color);
+        }
+    }
+
+    /// Draw the sigil points and their numbers onto an exported image
+    fn export_nodes(
+        &self,
+        image: &mut Image,
+        transform_point: &impl Fn(Vec2) -> (u32, u32),
+        scale: f32,
+        animation: Option<(usize, f32)>,
+    ) {
+        let emphasized = self.emphasized_number();
+        for (i, point) in self.points.iter().enumerate() {
+            // Nodes popping in during the animation grow from nothing
+            let scale = scale * self.node_scale(i, animation);
+            if scale <= 0.0 {
+                continue;
+            }
+            let (x, y) = transform_point(point.relative_pos);
+            let color = self.style_ink(self.node_color(i));
+            draw_circle_on_image(image, x, y, (self.node_radius(point, None) * scale) as u32, color);
+            // Ring nodes sharing the power number in gold
+            if emphasized == Some(point.number) {
+                draw_circle_on_image(image, x, y, (self.node_radius(point, emphasized) * scale) as u32, self.style_ink(GOLD));
+            }
+            // Draw the number inside the node, about as tall as it is on screen
+            let pixel_size = ((self.node_radius(point, None) * scale * 1.1 / 5.0).round() as u32).max(1);
+            draw_digit_on_image(image, x, y, point.number, pixel_size, color);
+        }
+    }
+
+    /// Work out where to save the sigil, creating directories as needed.
+    /// An explicit output path is never overwritten unless forced.
+    fn output_filename(&self) -> std::io::Result<String> {
+        let path = self.output_target()?;
+        self.check_overwrite(&path)?;
+        Ok(path.to_string_lossy().into_owned())
+    }
+
+    /// Work out where to save the sigil with another extension, guarding the final path
+    fn output_filename_as(&self, extension: &str) -> std::io::Result<PathBuf> {
+        let path = self.output_target()?.with_extension(extension);
+        self.check_overwrite(&path)?;
+        Ok(path)
+    }
+
+    /// Refuse to replace an existing file at an explicit output path unless forced.
+    /// Timestamped names never collide, so they aren't checked
+    fn check_overwrite(&self, path: &Path) -> std::io::Result<()> {
+        if self.output_path.is_some() && !self.force_overwrite && path.exists() {
+            return Err(std::io::Error::new(
+                std::io::ErrorKind::AlreadyExists,
+                format!("{} already exists (use --force to overwrite)", path.display()),
+            ));
+        }
+        Ok(())
+    }
+
+    /// Work out the base path to save under, creating directories as needed, without
+    /// checking whether it exists. Exports that write several files derive theirs from it
+    fn output_target(&self) -> std::io::Result<PathBuf> {
+        if let Some(path) = &self.output_path {
+            if let Some(parent) = path.parent().filter(|p| !p.as_os_str().is_empty()) {
+                std::fs::create_dir_all(parent)?;
+            }
+            return Ok(path.clone());
+        }
+
+        // Create the output directory and any missing parents (a no-op if it's already there)
+        let dir = self.save_dir();
+        std::fs::create_dir_all(&dir)?;
+
+        // Generate a filename with timestamp and sanitized intention
+        let timestamp = chrono::Local::now().format("%Y%m%d_%H%M%S");
+        let mut sanitized_intention = self.intention
+            .chars()
+            .filter(|c| c.is_ascii_alphanumeric())
+            .take(FILENAME_INTENTION_LEN)
+            .collect::<String>();
+        // An intention of only symbols or other scripts leaves nothing to name the file after
+        if sanitized_intention.is_empty() {
+            sanitized_intention = "sigil".to_string();
+        }
+        let filename = format!("sigil_{}_{}.png", timestamp, sanitized_intention);
+        Ok(dir.join(filename))
+    }
+
+    /// Get the directory saved files go in: the folder of an explicit output file, or the
+    /// output directory
+    fn save_dir(&self) -> PathBuf {
+        match &self.output_path {
+            Some(path) => path.parent().unwrap_or(Path::new("")).to_path_buf(),
+            None => self.output_dir.clone(),
+        }
+    }
+
+    /// Remove the output directory on exit if this session created it and never saved anything
+    fn clean_up(&self) {
+        let dir = self.save_dir();
+        if self.output_dir_existed || dir.as_os_str().is_empty() {
+            return;
+        }
+        let is_empty = std::fs::read_dir(&dir).map(|mut entries| entries.next().is_none()).unwrap_or(false);
+        if is_empty {
+            if let Err(e) = std::fs::remove_dir(&dir) {
+                eprintln!("Failed to remove empty output directory {}: {}", dir.display(), e);
+            }
+        }
+    }
+
+    /// Save exactly what's in the window right now, overlays and all, as a PNG
+    /// next to where sigils are saved, and return its path
+    fn save_screenshot(&self) -> std::io::Result<PathBuf> {
+        let dir = self.save_dir();
+        if !dir.as_os_str().is_empty() {
+            std::fs::create_dir_all(&dir)?;
+        }
+        let timestamp = chrono::Local::now().format("%Y%m%d_%H%M%S_%3f");
+        let path = dir.join(format!("screenshot_{}.png", timestamp));
+        let mut image = get_screen_data();
+        // The framebuffer comes back bottom row first and may not be fully opaque
+        let row = image.width as usize * 4;
+        let flipped: Vec<u8> = image.bytes.chunks(row).rev().flatten().copied().collect();
+        image.bytes = flipped;
+        for pixel in image.bytes.chunks_mut(4) {
+            pixel[3] = 255;
+        }
+        write_png(&image, &path, &[("Software", "sigil-gen")])?;
+        Ok(path)
+    }
+
+    /// Helper to get the ordered (start, end) indices of the current selection, whichever
+    /// way it was made. An anchor sitting on the cursor selects nothing, so gives `None`
+    fn selection_range(&self) -> Option<(usize, usize)> {
+        self.selection_start
+            .filter(|&start| start != self.cursor_pos)
+            .map(|start| (start.min(self.cursor_pos), start.max(self.cursor_pos)))
+    }
+
+    /// Helper to delete the current selection, if any, and return true if something was deleted.
+    /// The anchor is always cleared, so an empty selection can't grow over newly typed text
+    fn delete_selection(&mut self) -> bool {
+        let range = self.selection_range();
+        self.selection_start = None;
+        if let Some((start, end)) = range {
+            self.intention.drain(start..end);
+            self.cursor_pos = start;
+            true
+        } else {
+            false
+        }
+    }
+
+    /// Replace the selection, if any, with `text` and leave the cursor after it. The selection
+    /// doesn't count against the length limit, and whatever would go past it is dropped
+    fn insert_text(&mut self, text: &str) {
+        self.delete_selection();
+        let room = self.length_limit().saturating_sub(self.intention_len());
+        for ch in text.chars().take(room) {
+            self.intention.insert(self.cursor_pos, ch);
+            self.cursor_pos += ch.len_utf8();
+        }
+    }
+
+    /// Get the position of the character boundary before `pos` in the intention
+    fn prev_char_boundary(&self, pos: usize) -> usize {
+        self.intention[..pos].chars().next_back().map_or(0, |c| pos - c.len_utf8())
+    }
+
+    /// Get the position of the character boundary after `pos` in the intention
+    fn next_char_boundary(&self, pos: usize) -> usize {
+        self.intention[pos..].chars().next().map_or(pos, |c| pos + c.len_utf8())
+    }
+
+    /// Get the length of the intention in characters, which is what the limits count
+    fn intention_len(&self) -> usize {
+        self.intention.chars().count()
+    }
+
+    /// Get the start of the word before the cursor, skipping any whitespace first
+    fn word_boundary_left(&self) -> usize {
+        let mut pos = self.cursor_pos;
+        let mut chars = self.intention[..pos].chars().rev().peekable();
+        while let Some(c) = chars.next_if(|c| c.is_whitespace()) {
+            pos -= c.len_utf8();
+        }
+        while let Some(c) = chars.next_if(|c| c.is_alphanumeric()) {
+            pos -= c.len_utf8();
+        }
+        pos
+    }
+
+    /// Get the end of the word after the cursor, skipping any whitespace first
+    fn word_boundary_right(&self) -> usize {
+        let mut pos = self.cursor_pos;
+        let mut chars = self.intention[pos..].chars().peekable();
+        while let Some(c) = chars.next_if(|c| c.is_whitespace()) {
+            pos += c.len_utf8();
+        }
+        while let Some(c) = chars.next_if(|c| c.is_alphanumeric()) {
+            pos += c.len_utf8();
+        }
+        pos
+    }
+
+    /// Get the range of the word (or run of spaces) around a position in the intention,
+    /// the way a double-click selects it
+    fn word_at(&self, index: usize) -> (usize, usize) {
+        let text = &self.intention;
+        // Look at the character under the pointer, or the one before it at the end of the text
+        let Some(ch) = text[index..].chars().next().or_else(|| text[..index].chars().next_back()) else {
+            return (index, index);
+        };
+        let same_kind = |c: &char| *c != '\n' && c.is_alphanumeric() == ch.is_alphanumeric();
+        let before: usize = text[..index].chars().rev().take_while(same_kind).map(char::len_utf8).sum();
+        let after: usize = text[index..].chars().take_while(same_kind).map(char::len_utf8).sum();
+        (index - before, index + after)
+    }
+
+    /// Save the intention and cursor before an edit so it can be undone
+    fn push_undo(&mut self) {
+        self.undo_stack.push((self.intention.clone(), self.cursor_pos));
+        if self.undo_stack.len() > UNDO_LIMIT {
+            self.undo_stack.remove(0);
+        }
+        self.redo_stack.clear();
+        self.typing_run = None;
+    }
+
+    /// Step back to the intention before the last edit
+    fn undo(&mut self) {
+        if let Some((intention, cursor)) = self.undo_stack.pop() {
+            let current = (std::mem::replace(&mut self.intention, intention), self.cursor_pos);
+            self.redo_stack.push(current);
+            self.cursor_pos = cursor;
+            self.selection_start = None;
+            self.typing_run = None;
+        }
+    }
+
+    /// Reapply the last undone edit
+    fn redo(&mut self) {
+        if let Some((intention, cursor)) = self.redo_stack.pop() {
+            let current = (std::mem::replace(&mut self.intention, intention), self.cursor_pos);
+            self.undo_stack.push(current);
+            self.cursor_pos = cursor;
+            self.selection_start = None;
+            self.typing_run = None;
+        }
+    }
+
+    /// Get the current value of a setting for display
+    fn setting_value(&self, setting: Setting) -> String {
+        let on_off = |on: bool| if on { "On" } else { "Off" }.to_string();
+        match setting {
+            Setting::Frame => match self.frame {
+                Frame::Circle => "Circle".to_string(),
+                Frame::Polygon(sides) => format!("{}-sided polygon", sides),
+            },
+            Setting::AngleMode => format!("{:?}", self.angle_mode),
+            Setting::RadialMode => on_off(self.radial_mode),
+            Setting::PreserveOrder => on_off(self.preserve_order),
+            Setting::GhostVowels => on_off(self.ghost_vowels),
+            Setting::KeepVowels => on_off(self.keep_vowels),
+            Setting::NumberScheme => format!("{:?}", self.number_scheme),
+            Setting::CollapseRepeats => on_off(self.collapse_repeats),
+            Setting::EmphasizePower => on_off(self.emphasize_power),
+            Setting::NodeColors => format!("{:?}", self.node_color_mode),
+            Setting::SegmentColors => format!("{:?}", self.segment_color_mode),
+            Setting::SizeByValue => on_off(self.size_by_value),
+            Setting::CloseLoop => on_off(self.close_loop),
+            Setting::Curved => on_off(self.curved),
+            Setting::NodesBehindLines => on_off(self.nodes_behind_lines),
+            Setting::DropShadow => on_off(self.drop_shadow),
+            Setting::Glow => on_off(self.glow),
+            Setting::Sketchy => on_off(self.sketchy),
+            Setting::Spokes => on_off(self.spokes),
+            Setting::Theme => self.themes[self.theme_index].0.clone(),
+            Setting::Invert => on_off(self.invert),
+            Setting::Breathe => on_off(self.breathe),
+            Setting::PopInNodes => on_off(self.pop_in_nodes),
+            Setting::SnapGrid => format!("{:?}", self.snap_grid),
+            Setting::ExportSize => format!("{}px", self.export_size),
+            Setting::ExportStyle => format!("{:?}", self.export_style),
+            Setting::TransparentExport => on_off(self.export_transparent),
+            Setting::SrgbExport => on_off(self.srgb_export),
+            Setting::SaveJson => on_off(self.save_json),
+            Setting::ShowStats => on_off(self.show_stats),
+        }
+    }
+
+    /// Toggle a setting, or move it on to its next option
+    fn change_setting(&mut self, setting: Setting) {
+        match setting {
+            Setting::Frame => self.frame = self.frame.next(),
+            Setting::AngleMode => self.angle_mode = self.angle_mode.next(),
+            Setting::RadialMode => self.radial_mode = !self.radial_mode,
+            Setting::PreserveOrder => self.preserve_order = !self.preserve_order,
+            Setting::GhostVowels => self.ghost_vowels = !self.ghost_vowels,
+            Setting::KeepVowels => self.keep_vowels = !self.keep_vowels,
+            Setting::NumberScheme => self.number_scheme = self.number_scheme.next(),
+            Setting::CollapseRepeats => self.collapse_repeats = !self.collapse_repeats,
+            Setting::EmphasizePower => self.emphasize_power = !self.emphasize_power,
+            Setting::NodeColors => self.node_color_mode = self.node_color_mode.next(),
+            Setting::SegmentColors => self.segment_color_mode = self.segment_color_mode.next(),
+            Setting::SizeByValue => self.size_by_value = !self.size_by_value,
+            Setting::CloseLoop => self.close_loop = !self.close_loop,
+            Setting::Curved => self.curved = !self.curved,
+            Setting::NodesBehindLines => self.nodes_behind_lines = !self.nodes_behind_lines,
+            Setting::DropShadow => self.drop_shadow = !self.drop_shadow,
+            Setting::Glow => self.glow = !self.glow,
+            Setting::Sketchy => self.sketchy = !self.sketchy,
+            Setting::Spokes => self.spokes = !self.spokes,
+            Setting::Theme => self.cycle_theme(),
+            Setting::Invert => self.invert = !self.invert,
+            Setting::Breathe => self.breathe = !self.breathe,
+            Setting::PopInNodes => self.pop_in_nodes = !self.pop_in_nodes,
+            Setting::SnapGrid => self.snap_grid = self.snap_grid.next(),
+            Setting::ExportSize => self.cycle_export_size(),
+            Setting::ExportStyle => self.export_style = self.export_style.next(),
+            Setting::TransparentExport => self.export_transparent = !self.export_transparent,
+            Setting::SrgbExport => self.srgb_export = !self.srgb_export,
+            Setting::SaveJson => self.save_json = !self.save_json,
+            Setting::ShowStats => self.show_stats = !self.show_stats,
+        }
+    }
+
+    /// Helper to check if Ctrl is held
+    fn ctrl_down() -> bool {
+        is_key_down(KeyCode::LeftControl) || is_key_down(KeyCode::RightControl)
+    }
+
+    /// Helper to check if Shift is held
+    fn shift_down() -> bool {
+        is_key_down(KeyCode::LeftShift) || is_key_down(KeyCode::RightShift)
+    }
+
+    /// Helper to check if `+` was pressed (on the `=` key or the keypad)
+    fn speed_up_pressed() -> bool {
+        is_key_pressed(KeyCode::Equal) || is_key_pressed(KeyCode::KpAdd)
+    }
+
+    /// Helper to check if `-` was pressed (on the main keys or the keypad)
+    fn slow_down_pressed() -> bool {
+        is_key_pressed(KeyCode::Minus) || is_key_pressed(KeyCode::KpSubtract)
+    }
+
+    /// Make the animation one step faster or slower, within the allowed range
+    fn change_animation_speed(&mut self, faster: bool) {
+        let speed = if faster {
+            self.animation_speed * ANIMATION_SPEED_STEP
+        } else {
+            self.animation_speed / ANIMATION_SPEED_STEP
+        };
+        self.animation_speed = speed.clamp(MIN_ANIMATION_SPEED, MAX_ANIMATION_SPEED);
+    }
+
+    /// Zoom the sigil on screen one step in or out, within the allowed range
+    fn change_zoom(&mut self, zoom_in: bool) {
+        let zoom = if zoom_in { self.zoom * ZOOM_STEP } else { self.zoom / ZOOM_STEP };
+        self.zoom = zoom.clamp(MIN_ZOOM, MAX_ZOOM);
+        let message = format!("Zoom: {:.0}%", self.zoom * 100.0);
+        self.show_status(&message);
+    }
+
+    /// Handle the zoom controls: Ctrl with +/- zooms in and out and Ctrl+0 goes back to
+    /// the normal size. Returns whether any of them was pressed
+    fn handle_zoom_keys(&mut self) -> bool {
+        if !Self::ctrl_down() {
+            return false;
+        }
+        if Self::speed_up_pressed() || Self::slow_down_pressed() {
+            self.change_zoom(Self::speed_up_pressed());
+        } else if is_key_pressed(KeyCode::Key0) || is_key_pressed(KeyCode::Kp0) {
+            self.zoom = 1.0;
+            self.show_status("Zoom: 100%");
+        } else {
+            return false;
+        }
+        true
+    }
+
+    /// Get the camera that draws the sigil at the current zoom, scaled around the center
+    fn zoom_camera(&self) -> Camera2D {
+        let mut camera = Camera2D::from_display_rect(Rect::new(0.0, 0.0, screen_width(), screen_height()));
+        camera.zoom *= self.zoom;
+        camera
+    }
+
+    /// Get the position relative to the circle's center (matching `relative_pos`) that a
+    /// point on the screen falls on, taking the zoom into account
+    fn screen_to_relative(&self, screen: Vec2) -> Vec2 {
+        (screen - self.get_center()) / self.zoom
+    }
+
+    /// Longest intention allowed in the current input mode
+    fn length_limit(&self) -> usize {
+        if self.multiline {
+            MULTILINE_MAX_LEN
+        } else {
+            self.max_intention_len
+        }
+    }
+
+    /// Split the intention into the lines shown in the multi-line input box, breaking at
+    /// newlines and wrapping anything wider than the box. Returns the byte range of each line
+    fn wrapped_lines(&self) -> Vec<(usize, usize)> {
+        let mut lines = Vec::new();
+        let mut start = 0;
+        let mut width = 0.0;
+        for (i, ch) in self.intention.char_indices() {
+            if ch == '\n' {
+                lines.push((start, i));
+                start = i + 1;
+                width = 0.0;
+                continue;
+            }
+            let ch_width = measure_text(&self.intention[i..i + ch.len_utf8()], Some(self.font), 20, 1.0).width;
+            if width + ch_width > INPUT_WIDTH && i > start {
+                lines.push((start, i));
+                start = i;
+                width = 0.0;
+            }
+            width += ch_width;
+        }
+        lines.push((start, self.intention.len()));
+        lines
+    }
+
+    /// Get where the intention is drawn on the input screen: the left end of the first line's baseline
+    fn input_text_origin(&self) -> Vec2 {
+        self.get_center() + vec2(-200.0, -100.0)
+    }
+
+    /// Get the text position closest to a point on the input screen, or `None` if the
+    /// point isn't on the text box
+    fn input_index_at(&self, point: Vec2) -> Option<usize> {
+        let origin = self.input_text_origin();
+        // Text is drawn up from its baseline, so each line's row starts above it
+        let top = origin.y - 20.0;
+        if point.y < top || point.x < origin.x - 10.0 || point.x > origin.x + INPUT_WIDTH + 10.0 {
+            return None;
+        }
+        let row = ((point.y - top) / INPUT_LINE_HEIGHT) as usize;
+        if !self.multiline {
+            return (row == 0).then(|| self.cursor_index_at_x(point.x, origin.x));
+        }
+        let &line = self.wrapped_lines().get(row)?;
+        Some(self.index_at_x(line, point.x - origin.x))
+    }
+
+    /// Get the cursor position in the single-line input box closest to a click at `x`,
+    /// when the part of the text scrolled into view is drawn from `text_x`
+    fn cursor_index_at_x(&self, x: f32, text_x: f32) -> usize {
+        self.index_at_x((self.input_shown_start(), self.input_visible_end()), x - text_x)
+    }
+
+    /// Get the text position closest to a point anywhere on screen, for dragging a selection
+    /// out of the text box. Past either end of the single-line box it reaches one character
+    /// beyond what's shown, so holding the mouse there keeps scrolling the text
+    fn input_index_near(&self, point: Vec2) -> usize {
+        let origin = self.input_text_origin();
+        if self.multiline {
+            let lines = self.wrapped_lines();
+            let row = ((point.y - (origin.y - 20.0)) / INPUT_LINE_HEIGHT).max(0.0) as usize;
+            return self.index_at_x(lines[row.min(lines.len() - 1)], point.x - origin.x);
+        }
+        if point.x < origin.x {
+            self.prev_char_boundary(self.input_shown_start())
+        } else if point.x > origin.x + INPUT_WIDTH {
+            self.next_char_boundary(self.input_visible_end())
+        } else {
+            self.cursor_index_at_x(point.x, origin.x)
+        }
+    }
+
+    /// Get the first character shown in the single-line input box. The scroll is only
+    /// brought up to date once a frame, so one left behind by a change of intention
+    /// (that would cut the text somewhere invalid) starts from the beginning instead
+    fn input_shown_start(&self) -> usize {
+        if self.input_scroll <= self.cursor_pos && self.intention.is_char_boundary(self.input_scroll) {
+            self.input_scroll
+        } else {
+            0
+        }
+    }
+
+    /// Get the end of the part of the intention that fits in the single-line input box
+    /// after the first character shown
+    fn input_visible_end(&self) -> usize {
+        let start = self.input_shown_start();
+        let mut width = 0.0;
+        for (i, ch) in self.intention[start..].char_indices() {
+            let index = start + i;
+            width += measure_text(&self.intention[index..index + ch.len_utf8()], Some(self.font), 20, 1.0).width;
+            if width > INPUT_WIDTH {
+                return index;
+            }
+        }
+        self.intention.len()
+    }
+
+    /// Scroll the single-line input box just far enough to keep the cursor in view,
+    /// without leaving empty space at the end while the text is scrolled
+    fn scroll_input_to_cursor(&mut self) {
+        self.input_scroll = if !self.multiline && self.intention.is_char_boundary(self.input_scroll) {
+            self.input_scroll.min(self.cursor_pos)
+        } else {
+            0
+        };
+        // Widths add up character by character, the same way the shown part is measured
+        let width = |app: &Self, from: usize, to: usize| -> f32 {
+            let text = &app.intention[from..to];
+            text.char_indices().map(|(i, ch)| measure_text(&text[i..i + ch.len_utf8()], Some(app.font), 20, 1.0).width).sum()
+        };
+        while width(self, self.input_scroll, self.cursor_pos) > INPUT_WIDTH {
+            self.input_scroll = self.next_char_boundary(self.input_scroll);
+        }
+        while self.input_scroll > 0 && width(self, self.prev_char_boundary(self.input_scroll), self.intention.len()) <= INPUT_WIDTH {
+            self.input_scroll = self.prev_char_boundary(self.input_scroll);
+        }
+    }
+
+    /// Get the character boundary in a line of the intention closest to `x` pixels from
+    /// the line's start
+    fn index_at_x(&self, (start, end): (usize, usize), x: f32) -> usize {
+        let widths = self.intention[start..end].char_indices().map(|(i, ch)| {
+            let index = start + i;
+            (index, measure_text(&self.intention[index..index + ch.len_utf8()], Some(self.font), 20, 1.0).width)
+        });
+        boundary_at_x(widths, end, x)
+    }
+
+    /// Index of the wrapped line holding the cursor (the later line when it sits on a wrap point)
+    fn cursor_line(lines: &[(usize, usize)], cursor: usize) -> usize {
+        lines.iter().rposition(|&(start, _)| start <= cursor).unwrap_or(0)
+    }
+
+    /// Move the cursor to the closest position on the wrapped line above (-1) or below (+1)
+    fn move_cursor_vertically(&mut self, direction: isize) {
+        let lines = self.wrapped_lines();
+        let current = Self::cursor_line(&lines, self.cursor_pos);
+        let Some(target) = current.checked_add_signed(direction).filter(|t| *t < lines.len()) else {
+            return;
+        };
+        let x = measure_text(&self.intention[lines[current].0..self.cursor_pos], Some(self.font), 20, 1.0).width;
+        let (start, end) = lines[target];
+        self.cursor_pos = (start..=end)
+            .filter(|&i| self.intention.is_char_boundary(i))
+            .min_by(|a, b| {
+                let width_a = measure_text(&self.intention[start..*a], Some(self.font), 20, 1.0).width;
+                let width_b = measure_text(&self.intention[start..*b], Some(self.font), 20, 1.0).width;
+                (width_a - x).abs().total_cmp(&(width_b - x).abs())
+            })
+            .unwrap_or(start);
+    }
+
+    /// Switch between single-line and multi-line input, flattening newlines and
+    /// trimming to the shorter limit when going back to a single line
+    fn toggle_multiline(&mut self) {
+        self.multiline = !self.multiline;
+        if !self.multiline {
+            self.intention = self.intention.replace('\n', " ").chars().take(self.max_intention_len).collect();
+            self.cursor_pos = self.cursor_pos.min(self.intention.len());
+            self.selection_start = None;
+        }
+    }
+
+    /// Handle typing into the salt field (digits only)
+    fn handle_salt_input(&mut self) {
+        while let Some(ch) = get_char_pressed() {
+            if let Some(digit) = ch.to_digit(10) {
+                if self.salt.to_string().len() < SALT_MAX_DIGITS {
+                    self.salt = self.salt * 10 + digit;
+                }
+            }
+        }
+        if is_key_pressed(KeyCode::Backspace) {
+            self.salt /= 10;
+        }
+    }
+
+    /// Handle text input, cursor movement, and selection
+    fn handle_text_input(&mut self) {
+        // Handle character input (letters and digits in any script, and space)
+        while let Some(ch) = get_char_pressed() {
+            if is_intention_char(ch) {
+                // A run of typing is undone in one step, until the cursor moves away
+                if self.typing_run != Some(self.cursor_pos) || self.selection_range().is_some() {
+                    self.push_undo();
+                }
+                self.insert_text(ch.encode_utf8(&mut [0; 4]));
+                self.typing_run = Some(self.cursor_pos);
+            }
+        }
+
+        // In multi-line mode Enter starts a new line (Ctrl+Enter submits instead)
+        if self.multiline && is_key_pressed(KeyCode::Enter) && !Self::ctrl_down() {
+            self.push_undo();
+            self.insert_text("\n");
+        }
+
+        // Handle backspace
+        if is_key_pressed(KeyCode::Backspace) && (self.selection_range().is_some() || self.cursor_pos > 0) {
+            self.push_undo();
+            if !self.delete_selection() {
+                self.cursor_pos = self.prev_char_boundary(self.cursor_pos);
+                self.intention.remove(self.cursor_pos);
+            }
+        }
+
+        // Handle delete
+        if is_key_pressed(KeyCode::Delete) && (self.selection_range().is_some() || self.cursor_pos < self.intention.len()) {
+            self.push_undo();
+            if !self.delete_selection() {
+                self.intention.remove(self.cursor_pos);
+            }
+        }
+
+        // Handle left/right arrows (with/without selection), by whole words with Ctrl
+        for key in [KeyCode::Left, KeyCode::Right] {
+            if !is_key_pressed(key) {
+                continue;
+            }
+            let target = match (key, Self::ctrl_down()) {
+                (KeyCode::Left, true) => self.word_boundary_left(),
+                (KeyCode::Left, false) => self.prev_char_boundary(self.cursor_pos),
+                (_, true) => self.word_boundary_right(),
+                (_, false) => self.next_char_boundary(self.cursor_pos),
+            };
+            if Self::shift_down() {
+                if target != self.cursor_pos && self.selection_start.is_none() {
+                    self.selection_start = Some(self.cursor_pos);
+                }
+            } else {
+                self.selection_start = None;
+            }
+            self.cursor_pos = target;
+        }
+
+        // Handle up/down arrows across wrapped lines in multi-line mode
+        for (key, direction) in [(KeyCode::Up, -1), (KeyCode::Down, 1)] {
+            if self.multiline && is_key_pressed(key) {
+                if is_key_down(KeyCode::LeftShift) || is_key_down(KeyCode::RightShift) {
                     if self.selection_start.is_none() {
                         self.selection_start = Some(self.cursor_pos);
                     }
-                    self.cursor_pos += 1;
+                } else {
+                    self.selection_start = None;
+                }
+                self.move_cursor_vertically(direction);
+            }
+        }
+
+        // Click to place the cursor, or double-click to select the word under the pointer
+        if is_mouse_button_pressed(MouseButton::Left) {
+            let (mouse_x, mouse_y) = mouse_position();
+            if let Some(index) = self.input_index_at(vec2(mouse_x, mouse_y)) {
+                let now = get_time();
+                let double_click = self.last_click.is_some_and(|(time, at)| at == index && now - time < DOUBLE_CLICK_TIME);
+                if double_click {
+                    let (start, end) = self.word_at(index);
+                    self.selection_start = Some(start);
+                    self.cursor_pos = end;
+                    self.last_click = None;
+                } else {
+                    // Anchor a selection here, which dragging extends until the button is released
+                    self.selection_start = Some(index);
+                    self.cursor_pos = index;
+                    self.last_click = Some((now, index));
+                    self.drag_selecting = true;
+                }
+                self.typing_run = None;
+            }
+        }
+        if self.drag_selecting {
+            if is_mouse_button_down(MouseButton::Left) {
+                let (mouse_x, mouse_y) = mouse_position();
+                self.cursor_pos = self.input_index_near(vec2(mouse_x, mouse_y));
+            } else {
+                self.drag_selecting = false;
+                // A click without a drag leaves just the cursor
+                if self.selection_range().is_none() {
+                    self.selection_start = None;
+                }
+            }
+        }
+
+        // Handle Home/End keys
+        if is_key_pressed(KeyCode::Home) {
+            if is_key_down(KeyCode::LeftShift) || is_key_down(KeyCode::RightShift) {
+                if self.selection_start.is_none() {
+                    self.selection_start = Some(self.cursor_pos);
+                }
+            } else {
+                self.selection_start = None;
+            }
+            self.cursor_pos = 0;
+        }
+        if is_key_pressed(KeyCode::End) {
+            if is_key_down(KeyCode::LeftShift) || is_key_down(KeyCode::RightShift) {
+                if self.selection_start.is_none() {
+                    self.selection_start = Some(self.cursor_pos);
+                }
+            } else {
+                self.selection_start = None;
+            }
+            self.cursor_pos = self.intention.len();
+        }
+
+        // Handle Ctrl+A (Select All)
+        if is_key_pressed(KeyCode::A) && Self::ctrl_down() {
+            self.selection_start = Some(0);
+            self.cursor_pos = self.intention.len();
+        }
+
+        // Handle Ctrl+C (Copy)
+        if is_key_pressed(KeyCode::C) && Self::ctrl_down() {
+            if let Some((start, end)) = self.selection_range() {
+                if let Err(e) = write_clipboard(&self.intention[start..end]) {
+                    eprintln!("Failed to copy to clipboard: {}", e);
+                }
+            }
+        }
+
+        // Handle Ctrl+V (Paste), keeping only the characters that can be typed
+        if is_key_pressed(KeyCode::V) && Self::ctrl_down() {
+            let paste_text: String = read_clipboard()
+                .unwrap_or_default()
+                .chars()
+                .map(|ch| if ch.is_whitespace() { ' ' } else { ch })
+                .filter(|ch| is_intention_char(*ch))
+                .collect();
+            if !paste_text.is_empty() {
+                self.push_undo();
+                self.insert_text(&paste_text);
+            }
+        }
+
+        // Handle Ctrl+X (Cut)
+        if is_key_pressed(KeyCode::X) && Self::ctrl_down() {
+            if let Some((start, end)) = self.selection_range() {
+                if let Err(e) = write_clipboard(&self.intention[start..end]) {
+                    eprintln!("Failed to copy to clipboard: {}", e);
+                }
+                self.push_undo();
+                self.intention.drain(start..end);
+                self.cursor_pos = start;
+                self.selection_start = None;
+            }
+        }
+
+        // Handle Ctrl+Z (Undo) and Ctrl+Y or Ctrl+Shift+Z (Redo)
+        if is_key_pressed(KeyCode::Z) && Self::ctrl_down() {
+            if Self::shift_down() {
+                self.redo();
+            } else {
+                self.undo();
+            }
+        }
+        if is_key_pressed(KeyCode::Y) && Self::ctrl_down() {
+            self.redo();
+        }
+    }
+
+    /// Update the application state each frame
+    fn update(&mut self) {
+        self.blink_timer += get_frame_time();
+
+        self.status_timer = (self.status_timer - get_frame_time()).max(0.0);
+
+        // Handle save timer
+        if matches!(self.state, State::Saving) {
+            self.save_timer += get_frame_time();
+            if self.save_timer > self.save_message_time() {
+                self.state = State::Display;
+                self.save_timer = 0.0;
+            }
+        }
+
+        // While focus locked, ignore everything except holding Escape to unlock
+        if self.focus_locked {
+            while get_char_pressed().is_some() {}
+            if is_key_down(KeyCode::Escape) {
+                self.unlock_hold += get_frame_time();
+                if self.unlock_hold >= FOCUS_UNLOCK_HOLD {
+                    self.focus_locked = false;
+                    self.unlock_hold = 0.0;
+                }
+            } else {
+                self.unlock_hold = 0.0;
+            }
+            self.step_animation();
+            return;
+        }
+
+        // Open the help from anywhere it won't interrupt a save, or close it again.
+        // H is a letter like any other while typing, so only F1 works then
+        let typing = matches!(self.state, State::Input | State::Note { .. });
+        let closing = matches!(self.state, State::Help { .. }) && is_key_pressed(KeyCode::Escape);
+        if is_key_pressed(KeyCode::F1) || (is_key_pressed(KeyCode::H) && !typing) || closing {
+            self.toggle_help();
+            return;
+        }
+        // Toggle the debug overlay from any state
+        if is_key_pressed(KeyCode::F3) {
+            self.debug_overlay = !self.debug_overlay;
+        }
+        // Freeze the RNG to the current seed (or a new one) for the rest of the session
+        if is_key_pressed(KeyCode::F5) {
+            self.frozen_seed = match self.frozen_seed {
+                Some(_) => None,
+                None if self.points.is_empty() => Some(fresh_seed()),
+                None => Some(self.seed),
+            };
+        }
+
+        // State machine for the app
+        match &mut self.state {
+            State::Loading => {}
+            State::Start => {
+                // Consume any character input
+                while get_char_pressed().is_some() {}
+                if is_key_pressed(KeyCode::Space) {
+                    self.state = State::Input;
+                } else if is_key_pressed(KeyCode::O) {
+                    self.load_latest();
+                } else if is_key_pressed(KeyCode::S) {
+                    self.state = State::Settings { selected: 0 };
+                } else if is_key_pressed(KeyCode::T) {
+                    self.cycle_theme();
+                } else if is_key_pressed(KeyCode::G) {
+                    self.open_gallery();
+                } else if is_key_pressed(KeyCode::Delete) && self.show_stats {
+                    self.stats.reset();
+                    self.stats.save();
+                } else if is_key_pressed(KeyCode::Escape) {
+                    self.quitting = true;
+                }
+            }
+            State::Input => {
+                // Handle text input and editing
+                if is_key_pressed(KeyCode::F6) {
+                    self.toggle_multiline();
+                }
+                if is_key_pressed(KeyCode::F7) {
+                    self.keep_vowels = !self.keep_vowels;
+                }
+                if is_key_pressed(KeyCode::F8) {
+                    self.number_scheme = self.number_scheme.next();
+                }
+                if is_key_pressed(KeyCode::Tab) {
+                    self.salt_focused = !self.salt_focused;
+                }
+                // Clicking the text box moves the focus back to it from the salt
+                if self.salt_focused && is_mouse_button_pressed(MouseButton::Left) {
+                    let (mouse_x, mouse_y) = mouse_position();
+                    self.salt_focused = self.input_index_at(vec2(mouse_x, mouse_y)).is_none();
+                }
+                if self.salt_focused {
+                    self.handle_salt_input();
+                } else {
+                    self.handle_text_input();
+                }
+                self.scroll_input_to_cursor();
+                let newline_mode = self.multiline && !self.salt_focused;
+                let submit = is_key_pressed(KeyCode::Enter) && (!newline_mode || Self::ctrl_down());
+                if submit && !self.intention.trim().is_empty() {
+                    self.generate_sigil();
+                } else if is_key_pressed(KeyCode::Escape) {
+                    // The text is kept, so coming back carries on where it left off
+                    self.salt_focused = false;
+                    self.state = State::Start;
+                }
+            }
+            State::Display => {
+                // Consume any character input
+                while get_char_pressed().is_some() {}
+                // Hold Q to swap in the previous sigil, releasing to return to this one
+                if is_key_down(KeyCode::Q) != self.showing_previous {
+                    self.swap_previous();
+                }
+                if self.showing_previous {
+                    return;
+                }
+                // Scroll the mouse wheel to zoom the sigil in and out
+                let (_, wheel_y) = mouse_wheel();
+                if wheel_y != 0.0 {
+                    self.change_zoom(wheel_y > 0.0);
+                }
+                // Nodes can be dragged by hand unless the layout is locked
+                if !self.locked {
+                    self.handle_node_drag();
+                }
+                if is_key_pressed(KeyCode::Space) && self.points.len() > 1 {
+                    self.state = State::Animating { progress: 0.0, line: 0, reverse: false, paused: false };
+                } else if is_key_pressed(KeyCode::L) {
+                    self.locked = !self.locked;
+                } else if is_key_pressed(KeyCode::A) && Self::shift_down() && !self.locked {
+                    self.angle_mode = self.angle_mode.next();
+                    self.place_points();
+                } else if is_key_pressed(KeyCode::A) && !self.locked {
+                    self.regenerate_angles();
+                } else if self.handle_zoom_keys() {
+                    // Zooming with Ctrl held takes the place of changing the speed
+                } else if Self::speed_up_pressed() || Self::slow_down_pressed() {
+                    self.change_animation_speed(Self::speed_up_pressed());
+                    let message = format!("Animation speed: {:.1}x", self.animation_speed);
+                    self.show_status(&message);
+                } else if is_key_pressed(KeyCode::G) && Self::shift_down() {
+                    self.open_gallery();
+                } else if is_key_pressed(KeyCode::G) && !self.locked {
+                    // A frozen seed would only give the same sigil back
+                    if self.frozen_seed.is_some() {
+                        self.show_status("Seed is frozen (F5 to unfreeze)");
+                    } else {
+                        self.generate_sigil();
+                    }
+                } else if is_key_pressed(KeyCode::R) && !self.locked {
+                    self.state = State::ConfirmReset;
+                } else if is_key_pressed(KeyCode::P) {
+                    self.emphasize_power = !self.emphasize_power;
+                } else if is_key_pressed(KeyCode::V) {
+                    self.ghost_vowels = !self.ghost_vowels;
+                    self.generate_ghost_points();
+                } else if is_key_pressed(KeyCode::LeftBracket) {
+                    self.ring_width = (self.ring_width - 1.0).max(MIN_RING_WIDTH);
+                } else if is_key_pressed(KeyCode::RightBracket) {
+                    self.ring_width = (self.ring_width + 1.0).min(MAX_RING_WIDTH);
+                } else if is_key_pressed(KeyCode::N) {
+                    self.nodes_behind_lines = !self.nodes_behind_lines;
+                } else if is_key_pressed(KeyCode::D) && Self::shift_down() {
+                    self.glow = !self.glow;
+                } else if is_key_pressed(KeyCode::D) {
+                    self.drop_shadow = !self.drop_shadow;
+                } else if is_key_pressed(KeyCode::M) && Self::shift_down() {
+                    let result = self.save_sigil_gif();
+                    self.finish_save(result);
+                } else if is_key_pressed(KeyCode::M) {
+                    let result = self.save_sigil_svg();
+                    self.finish_save(result);
+                } else if is_key_pressed(KeyCode::Tab) {
+                    self.node_color_mode = self.node_color_mode.next();
+                } else if is_key_pressed(KeyCode::B) && Self::shift_down() {
+                    self.export_transparent = !self.export_transparent;
+                } else if is_key_pressed(KeyCode::B) {
+                    self.breathe = !self.breathe;
+                } else if is_key_pressed(KeyCode::Y) {
+                    self.segment_color_mode = self.segment_color_mode.next();
+                } else if is_key_pressed(KeyCode::Z) {
+                    self.size_by_value = !self.size_by_value;
+                } else if is_key_pressed(KeyCode::W) {
+                    self.sketchy = !self.sketchy;
+                } else if is_key_pressed(KeyCode::O) {
+                    self.spokes = !self.spokes;
+                } else if is_key_pressed(KeyCode::T) {
+                    self.cycle_theme();
+                    let message = format!("Theme: {}", self.themes[self.theme_index].0);
+                    self.show_status(&message);
+                } else if is_key_pressed(KeyCode::U) {
+                    self.curved = !self.curved;
+                } else if is_key_pressed(KeyCode::J) {
+                    self.close_loop = !self.close_loop;
+                } else if is_key_pressed(KeyCode::X) {
+                    self.cycle_export_size();
+                    let message = format!("Export size: {}px", self.export_size);
+                    self.show_status(&message);
+                } else if is_key_pressed(KeyCode::I) {
+                    self.invert = !self.invert;
+                } else if is_key_pressed(KeyCode::F) {
+                    self.focus_locked = true;
+                } else if is_key_pressed(KeyCode::E) {
+                    self.state = State::Note { original: self.note.clone() };
+                } else if is_key_pressed(KeyCode::K) {
+                    self.copy_sigil_code();
+                } else if is_key_pressed(KeyCode::C) {
+                    self.copy_sigil_image();
+                } else if is_key_pressed(KeyCode::S) && Self::shift_down() {
+                    if let Err(e) = self.start_size_export() {
+                        eprintln!("Failed to save sigil sizes: {}", e);
+                        self.show_status(&format!("Failed to save sigil sizes: {}", e));
+                    }
+                } else if is_key_pressed(KeyCode::S) {
+                    self.state = State::ChooseFormat;
+                } else if is_key_pressed(KeyCode::Escape) {
+                    // Back to the intention, to edit it and make the sigil again
+                    self.state = State::Input;
+                }
+            }
+            State::Animating { .. } => {
+                // Consume any character input
+                while get_char_pressed().is_some() {}
+                // Scroll the mouse wheel or press +/- to speed up or slow down the animation
+                let (_, wheel_y) = mouse_wheel();
+                if self.handle_zoom_keys() {
+                    // Zooming with Ctrl held takes the place of changing the speed
+                } else if wheel_y > 0.0 || Self::speed_up_pressed() {
+                    self.change_animation_speed(true);
+                } else if wheel_y < 0.0 || Self::slow_down_pressed() {
+                    self.change_animation_speed(false);
+                }
+                // Escape stops the animation (the only way out of a breathing loop)
+                if is_key_pressed(KeyCode::Escape) {
+                    self.state = State::Display;
+                    return;
+                } else if is_key_pressed(KeyCode::F) {
+                    self.focus_locked = true;
+                } else if is_key_pressed(KeyCode::Space) {
+                    if let State::Animating { paused, .. } = &mut self.state {
+                        *paused = !*paused;
+                    }
+                } else if is_key_pressed(KeyCode::Left) {
+                    self.scrub_animation(-1);
+                } else if is_key_pressed(KeyCode::Right) {
+                    self.scrub_animation(1);
+                }
+            }
+            State::Saving => {
+                // Consume any character input
+                while get_char_pressed().is_some() {}
+            }
+            State::Note { original } => {
+                // Type the note (printable ASCII only)
+                while let Some(ch) = get_char_pressed() {
+                    if (' '..='~').contains(&ch) && self.note.len() < NOTE_MAX_LEN {
+                        self.note.push(ch);
+                    }
+                }
+                if is_key_pressed(KeyCode::Backspace) {
+                    self.note.pop();
+                }
+                if is_key_pressed(KeyCode::Enter) {
+                    self.state = State::Display;
+                } else if is_key_pressed(KeyCode::Escape) {
+                    self.note = std::mem::take(original);
+                    self.state = State::Display;
+                }
+            }
+            State::Settings { selected } => {
+                // Consume any character input
+                while get_char_pressed().is_some() {}
+                let count = Setting::ALL.len();
+                if is_key_pressed(KeyCode::Up) {
+                    *selected = (*selected + count - 1) % count;
+                } else if is_key_pressed(KeyCode::Down) {
+                    *selected = (*selected + 1) % count;
+                } else if is_key_pressed(KeyCode::Enter) || is_key_pressed(KeyCode::Space) {
+                    let setting = Setting::ALL[*selected];
+                    self.change_setting(setting);
+                } else if is_key_pressed(KeyCode::Escape) {
+                    self.state = State::Start;
+                }
+            }
+            State::Gallery { selected, viewing } => {
+                // Consume any character input
+                while get_char_pressed().is_some() {}
+                let (selected, viewing) = (*selected, *viewing);
+                let count = self.gallery.len();
+                if viewing {
+                    if is_key_pressed(KeyCode::Escape) || is_key_pressed(KeyCode::Enter) {
+                        if let Some(image) = self.gallery_image.take() {
+                            image.delete();
+                        }
+                        self.state = State::Gallery { selected, viewing: false };
+                    } else if is_key_pressed(KeyCode::Left) {
+                        self.view_gallery_image((selected + count - 1) % count);
+                    } else if is_key_pressed(KeyCode::Right) {
+                        self.view_gallery_image((selected + 1) % count);
+                    } else if is_key_pressed(KeyCode::O) {
+                        self.open_gallery_record(selected);
+                    }
+                } else {
+                    let target = if is_key_pressed(KeyCode::Left) {
+                        selected.checked_sub(1)
+                    } else if is_key_pressed(KeyCode::Right) {
+                        Some(selected + 1)
+                    } else if is_key_pressed(KeyCode::Up) {
+                        selected.checked_sub(GALLERY_COLUMNS)
+                    } else if is_key_pressed(KeyCode::Down) {
+                        Some(selected + GALLERY_COLUMNS)
+                    } else {
+                        None
+                    };
+                    if let Some(target) = target.filter(|&t| t < count) {
+                        self.state = State::Gallery { selected: target, viewing: false };
+                    } else if is_key_pressed(KeyCode::Enter) {
+                        self.view_gallery_image(selected);
+                    } else if is_key_pressed(KeyCode::O) {
+                        self.open_gallery_record(selected);
+                    } else if is_key_pressed(KeyCode::Escape) {
+                        self.close_gallery();
+                        self.state = if self.points.is_empty() { State::Start } else { State::Display };
+                    }
+                }
+            }
+            State::Help { .. } => {
+                // Consume any character input (closing is handled above)
+                while get_char_pressed().is_some() {}
+            }
+            State::ConfirmReset => {
+                // Consume any character input
+                while get_char_pressed().is_some() {}
+                if is_key_pressed(KeyCode::Y) {
+                    self.reset();
+                } else if is_key_pressed(KeyCode::N) || is_key_pressed(KeyCode::Escape) {
+                    self.state = State::Display;
+                }
+            }
+            State::ChooseFormat => {
+                // Consume any character input
+                while get_char_pressed().is_some() {}
+                let format = if is_key_pressed(KeyCode::P) {
+                    Some(ExportFormat::Png)
+                } else if is_key_pressed(KeyCode::S) {
+                    Some(ExportFormat::Svg)
+                } else if is_key_pressed(KeyCode::J) {
+                    Some(ExportFormat::Jpeg)
+                } else if is_key_pressed(KeyCode::G) {
+                    Some(ExportFormat::Gif)
+                } else {
+                    None
+                };
+                if let Some(format) = format {
+                    let result = self.save_sigil_as(format);
+                    self.finish_save(result);
+                } else if is_key_pressed(KeyCode::Escape) {
+                    self.state = State::Display;
+                }
+            }
+            State::Exporting { .. } => {
+                // Consume any character input
+                while get_char_pressed().is_some() {}
+                self.step_size_export();
+            }
+        }
+        self.step_animation();
+    }
+
+    /// Copy the current sigil's share code to the clipboard (and print it, in case
+    /// the clipboard isn't available)
+    fn copy_sigil_code(&mut self) {
+        let code = self.sigil_code().encode();
+        println!("Sigil code: {}", code);
+        match write_clipboard(&code) {
+            Ok(()) => self.show_status("Copied sigil code"),
+            Err(e) => {
+                eprintln!("Failed to copy sigil code: {}", e);
+                self.show_status("Couldn't copy sigil code (printed to the console instead)");
+            }
+        }
+    }
+
+    /// Copy the sigil to the clipboard as an image, rendered just as it would be saved
+    fn copy_sigil_image(&mut self) {
+        match write_clipboard_image(&self.render_to_image()) {
+            Ok(()) => self.show_status("Copied sigil image"),
+            Err(e) => {
+                eprintln!("Failed to copy sigil image: {}", e);
+                self.show_status(&format!("Couldn't copy sigil image: {}", e));
+            }
+        }
+    }
+
+    /// Show a short status message on the sigil screen
+    fn show_status(&mut self, message: &str) {
+        self.status_message = message.to_string();
+        self.status_timer = STATUS_MESSAGE_DURATION;
+    }
+
+    /// Swap the current and previous sigils' points, if there is a previous sigil
+    fn swap_previous(&mut self) {
+        if let Some(prev_points) = &mut self.prev_points {
+            std::mem::swap(&mut self.points, prev_points);
+            self.showing_previous = !self.showing_previous;
+        }
+    }
+
+    /// Pick up a node with the mouse and move it, snapping to the grid while Shift is held
+    fn handle_node_drag(&mut self) {
+        let (mouse_x, mouse_y) = mouse_position();
+        let mouse = self.screen_to_relative(vec2(mouse_x, mouse_y));
+        if is_mouse_button_pressed(MouseButton::Left) {
+            let emphasized = self.emphasized_number();
+            self.dragging = self.points
+                .iter()
+                .position(|point| point.relative_pos.distance(mouse) <= self.node_radius(point, emphasized));
+        }
+        let Some(index) = self.dragging else {
+            return;
+        };
+        if !is_mouse_button_down(MouseButton::Left) || index >= self.points.len() {
+            self.dragging = None;
+            return;
+        }
+        self.points[index].relative_pos = if Self::shift_down() {
+            snap_to_grid(mouse, self.snap_grid)
+        } else {
+            mouse
+        };
+    }
+
+    /// Move the drawing animation along by one frame, if it's running
+    fn step_animation(&mut self) {
+        let segment_count = self.segment_count();
+        let delta = get_frame_time() * self.animation_speed;
+        let breathe = self.breathe;
+        let State::Animating { progress, line, reverse, paused } = &mut self.state else {
+            return;
+        };
+        if *paused {
+            return;
+        }
+        if *reverse {
+            match rewind_animation(*progress, *line, delta) {
+                Some((next_progress, next_line)) => {
+                    *progress = next_progress;
+                    *line = next_line;
+                }
+                // Fully un-drawn, so start drawing forward again
+                None => {
+                    *progress = 0.0;
+                    *line = 0;
+                    *reverse = false;
+                }
+            }
+        } else {
+            match advance_animation(*progress, *line, delta, segment_count) {
+                Some((next_progress, next_line)) => {
+                    *progress = next_progress;
+                    *line = next_line;
+                }
+                // Fully drawn, so either stop or start un-drawing from the end
+                None if breathe => {
+                    *progress = 1.0;
+                    *line = segment_count - 1;
+                    *reverse = true;
                 }
+                None => self.state = State::Display,
+            }
+        }
+    }
+
+    /// Step the animation a whole line forward (+1) or back (-1), pausing it so the
+    /// step can be studied. A part-drawn line counts as a step of its own
+    fn scrub_animation(&mut self, direction: isize) {
+        let segment_count = self.segment_count();
+        let State::Animating { progress, line, paused, .. } = &mut self.state else {
+            return;
+        };
+        *paused = true;
+        if direction > 0 {
+            if *line + 1 < segment_count {
+                *line += 1;
+                *progress = 0.0;
             } else {
-                if self.cursor_pos < self.intention.len() {
-                    self.cursor_pos += 1;
+                *progress = 1.0;
+            }
+        } else if *progress > 0.0 {
+            *progress = 0.0;
+        } else {
+            *line = line.saturating_sub(1);
+        }
+    }
+
+    /// Show the help screen over whatever is going on, or go back to it from the help
+    fn toggle_help(&mut self) {
+        // The key that opened or closed the help shouldn't be typed anywhere
+        while get_char_pressed().is_some() {}
+        self.state = match std::mem::replace(&mut self.state, State::Loading) {
+            State::Help { previous } => *previous,
+            state @ (State::Loading | State::Saving | State::Exporting { .. }) => state,
+            state => State::Help { previous: Box::new(state) },
+        };
+    }
+
+    /// Reset the app to the input state
+    fn reset(&mut self) {
+        self.state = State::Input;
+        self.intention.clear();
+        self.numbers.clear();
+        self.sources.clear();
+        self.points.clear();
+        self.ghost_points.clear();
+        self.note.clear();
+        self.blink_timer = 0.0;
+        self.cursor_pos = 0;
+        self.selection_start = None;
+    }
+
+    /// Draw the current frame
+    fn draw(&self) {
+        clear_background(self.ink(self.theme().background));
+        match &self.state {
+            State::Loading => self.draw_loading(),
+            State::Start => self.draw_start(),
+            State::Input => self.draw_input(),
+            State::Display => self.draw_sigil(None),
+            State::Animating { progress, line, .. } => self.draw_sigil(Some((*line, *progress))),
+            State::Saving => {
+                self.draw_sigil(None);
+                self.draw_saving_message();
+            }
+            State::Note { .. } => {
+                self.draw_sigil(None);
+                self.draw_note_entry();
+            }
+            State::ChooseFormat => {
+                self.draw_sigil(None);
+                self.draw_format_menu();
+            }
+            State::ConfirmReset => {
+                self.draw_sigil(None);
+                self.draw_reset_prompt();
+            }
+            State::Exporting { done, .. } => {
+                self.draw_sigil(None);
+                self.draw_export_progress(*done);
+            }
+            State::Settings { selected } => self.draw_settings(*selected),
+            State::Gallery { selected, viewing } => self.draw_gallery(*selected, *viewing),
+            State::Help { .. } => self.draw_help(),
+        }
+    }
+
+    /// Get how much to scale text by for the window's width, so it stays readable in
+    /// small windows without looking tiny in large ones
+    fn ui_scale(&self) -> f32 {
+        (screen_width() / UI_REFERENCE_WIDTH).clamp(MIN_UI_SCALE, MAX_UI_SCALE)
+    }
+
+    /// Get a font size scaled for the window's width
+    fn scaled_font(&self, size: u16) -> u16 {
+        (size as f32 * self.ui_scale()).round() as u16
+    }
+
+    /// Draw a line of text centered across the window at a font size scaled for its width,
+    /// shrinking it further if it would still run off the sides
+    fn draw_centered_text(&self, text: &str, y: f32, font_size: u16, color: Color) {
+        let mut font_size = self.scaled_font(font_size);
+        let room = screen_width() - 2.0 * UI_MARGIN;
+        let width = measure_text(text, Some(self.font), font_size, 1.0).width;
+        if width > room {
+            font_size = ((font_size as f32 * room / width) as u16).max(1);
+        }
+        let width = measure_text(text, Some(self.font), font_size, 1.0).width;
+        draw_text_ex(
+            text,
+            (screen_width() - width) / 2.0,
+            y,
+            TextParams {
+                font: self.font,
+                font_size,
+                color,
+                ..Default::default()
+            },
+        );
+    }
+
+    /// Draw hints separated by " | " along the bottom of the window, wrapped onto as many
+    /// lines as the window's width needs. Returns the y of the top line
+    fn draw_hint_lines(&self, hints: &str, font_size: u16, color: Color) -> f32 {
+        let font_size = self.scaled_font(font_size);
+        let room = screen_width() - 2.0 * UI_MARGIN;
+        let mut lines: Vec<String> = Vec::new();
+        for hint in hints.split(" | ") {
+            match lines.last_mut() {
+                Some(line) if measure_text(&format!("{} | {}", line, hint), Some(self.font), font_size, 1.0).width <= room => {
+                    line.push_str(" | ");
+                    line.push_str(hint);
+                }
+                _ => lines.push(hint.to_string()),
+            }
+        }
+        let line_height = font_size as f32 * 1.25;
+        let top = screen_height() - 30.0 - (lines.len() - 1) as f32 * line_height;
+        for (i, line) in lines.iter().enumerate() {
+            draw_text_ex(
+                line,
+                UI_MARGIN,
+                top + i as f32 * line_height,
+                TextParams {
+                    font: self.font,
+                    font_size,
+                    color,
+                    ..Default::default()
+                },
+            );
+        }
+        top
+    }
+
+    /// Draw the loading message shown while assets load
+    fn draw_loading(&self) {
+        self.draw_centered_text("Loading...", self.get_center().y, 24, self.ink(LIGHTGRAY));
+    }
+
+    /// Draw the start screen
+    fn draw_start(&self) {
+        let center = self.get_center();
+        let scale = self.ui_scale();
+        self.draw_centered_text("SIGIL GENERATOR", center.y - 50.0 * scale, 32, self.ink(WHITE));
+        self.draw_centered_text("Press SPACE to begin", center.y + 20.0 * scale, 24, self.ink(LIGHTGRAY));
+        self.draw_centered_text("O: Open last saved sigil | S: Settings | G: Gallery | H: Help | ESC: Quit", center.y + 60.0 * scale, 18, self.ink(GRAY));
+        self.draw_centered_text(
+            &format!("Theme: {} (T to change)", self.themes[self.theme_index].0),
+            center.y + 80.0 * scale,
+            16,
+            self.ink(self.theme().line),
+        );
+        if self.show_stats {
+            self.draw_centered_text(
+                &format!(
+                    "Sigils created: {} | Saved: {} (DEL to clear)",
+                    self.stats.generated, self.stats.saved
+                ),
+                center.y + 100.0 * scale,
+                16,
+                self.ink(DARKGRAY),
+            );
+        }
+    }
+
+    /// Draw the settings screen, highlighting the selected row
+    fn draw_settings(&self, selected: usize) {
+        let center = self.get_center();
+        draw_text_ex(
+            "SETTINGS",
+            center.x - 200.0,
+            center.y - 180.0,
+            TextParams {
+                font: self.font,
+                font_size: 32,
+                color: self.ink(WHITE),
+                ..Default::default()
+            },
+        );
+        // Scroll just far enough that the selected setting is on screen
+        let first = selected
+            .saturating_sub(SETTINGS_VISIBLE_ROWS - 1)
+            .min(Setting::ALL.len().saturating_sub(SETTINGS_VISIBLE_ROWS));
+        for (i, setting) in Setting::ALL.iter().enumerate().skip(first).take(SETTINGS_VISIBLE_ROWS) {
+            let y = center.y - 120.0 + (i - first) as f32 * 30.0;
+            let color = if i == selected { YELLOW } else { LIGHTGRAY };
+            if i == selected {
+                draw_rectangle(center.x - 210.0, y - 20.0, 420.0, 28.0, self.ink(Color::from_rgba(100, 150, 255, 60)));
+            }
+            draw_text_ex(
+                setting.label(),
+                center.x - 200.0,
+                y,
+                TextParams {
+                    font: self.font,
+                    font_size: 20,
+                    color,
+                    ..Default::default()
+                },
+            );
+            draw_text_ex(
+                &self.setting_value(*setting),
+                center.x + 80.0,
+                y,
+                TextParams {
+                    font: self.font,
+                    font_size: 20,
+                    color,
+                    ..Default::default()
+                },
+            );
+        }
+        draw_text_ex(
+            "UP/DOWN: Select | ENTER/SPACE: Change | ESC: Back",
+            center.x - 200.0,
+            center.y + 180.0,
+            TextParams {
+                font: self.font,
+                font_size: 16,
+                color: self.ink(GRAY),
+                ..Default::default()
+            },
+        );
+    }
+
+    /// Draw the help screen, listing every control in columns
+    fn draw_help(&self) {
+        let text = |text: &str, x: f32, y: f32, font_size: u16, color: Color| {
+            draw_text_ex(
+                text,
+                x,
+                y,
+                TextParams {
+                    font: self.font,
+                    font_size,
+                    color: self.ink(color),
+                    ..Default::default()
+                },
+            );
+        };
+        text("CONTROLS", UI_MARGIN, 40.0, 28, WHITE);
+        let columns = 3;
+        let column_width = (screen_width() - 2.0 * UI_MARGIN) / columns as f32;
+        let (top, bottom, line_height) = (75.0, screen_height() - 50.0, 15.0);
+        let (mut column, mut y) = (0, top);
+        for (title, controls) in HELP_SECTIONS {
+            // Long sections carry on in the next column, but a title always has a line under it
+            if y + line_height * 2.25 > bottom && column + 1 < columns {
+                column += 1;
+                y = top;
+            }
+            text(title, UI_MARGIN + column as f32 * column_width, y, 18, SKYBLUE);
+            y += line_height * 1.25;
+            for (keys, action) in controls {
+                if y > bottom && column + 1 < columns {
+                    column += 1;
+                    y = top;
+                }
+                let x = UI_MARGIN + column as f32 * column_width;
+                text(keys, x, y, 14, YELLOW);
+                text(action, x + column_width * 0.4, y, 14, LIGHTGRAY);
+                y += line_height;
+            }
+            y += line_height * 0.75;
+        }
+        text("ESC, H or F1: Back", UI_MARGIN, screen_height() - 30.0, 16, GRAY);
+    }
+
+    /// Draw the gallery: a scrolling grid of saved sigils with the selected one outlined,
+    /// or the selected one alone, scaled to fit the window
+    fn draw_gallery(&self, selected: usize, viewing: bool) {
+        let Some(entry) = self.gallery.get(selected) else {
+            return;
+        };
+        let text = |text: &str, x: f32, y: f32, size: u16, color: Color| {
+            draw_text_ex(
+                text,
+                x,
+                y,
+                TextParams {
+                    font: self.font,
+                    font_size: size,
+                    color: self.ink(color),
+                    ..Default::default()
+                },
+            );
+        };
+        let name = entry.path.file_name().unwrap_or_default().to_string_lossy();
+        let open = if entry.record.is_some() { " | O: Open" } else { "" };
+        if let (true, Some(image)) = (viewing, self.gallery_image) {
+            // Scale the image down to fit between the title and the hint, never up
+            let room = vec2(screen_width() - 40.0, screen_height() - 120.0);
+            let scale = (room.x / image.width()).min(room.y / image.height()).min(1.0);
+            let size = vec2(image.width(), image.height()) * scale;
+            let position = vec2((screen_width() - size.x) / 2.0, 60.0 + (room.y - size.y) / 2.0);
+            draw_texture_ex(image, position.x, position.y, WHITE, DrawTextureParams { dest_size: Some(size), ..Default::default() });
+            text(&name, 20.0, 40.0, 20, WHITE);
+            text(&format!("LEFT/RIGHT: Browse{} | ESC: Back", open), 20.0, screen_height() - 30.0, 16, GRAY);
+            return;
+        }
+        let cell = THUMBNAIL_SIZE as f32 + GALLERY_SPACING;
+        let row_height = cell + GALLERY_NOTE_HEIGHT;
+        let left = (screen_width() - cell * GALLERY_COLUMNS as f32) / 2.0;
+        let top = 80.0;
+        // Scroll by whole rows to keep the selection visible
+        let visible_rows = (((screen_height() - top - 60.0) / row_height) as usize).max(1);
+        let first_row = (selected / GALLERY_COLUMNS).saturating_sub(visible_rows - 1);
+        text("GALLERY", left, 40.0, 32, WHITE);
+        text(&name, left, 64.0, 16, LIGHTGRAY);
+        let shown = self.gallery.iter().enumerate().skip(first_row * GALLERY_COLUMNS).take(visible_rows * GALLERY_COLUMNS);
+        for (i, entry) in shown {
+            let column = (i % GALLERY_COLUMNS) as f32;
+            let row = (i / GALLERY_COLUMNS - first_row) as f32;
+            let cell_pos = vec2(left + column * cell, top + row * row_height);
+            let thumbnail = entry.thumbnail;
+            let offset = (vec2(THUMBNAIL_SIZE as f32, THUMBNAIL_SIZE as f32) - vec2(thumbnail.width(), thumbnail.height())) / 2.0;
+            draw_texture(thumbnail, cell_pos.x + offset.x, cell_pos.y + offset.y, WHITE);
+            if i == selected {
+                let size = THUMBNAIL_SIZE as f32 + 6.0;
+                draw_rectangle_lines(cell_pos.x - 3.0, cell_pos.y - 3.0, size, size, 2.0, self.ink(self.theme().line));
+            }
+            if !entry.note.is_empty() {
+                let note = self.fit_text(&entry.note.replace('\n', " "), 14, THUMBNAIL_SIZE as f32);
+                text(&note, cell_pos.x, cell_pos.y + THUMBNAIL_SIZE as f32 + 18.0, 14, LIGHTGRAY);
+            }
+        }
+        text(&format!("ARROWS: Select | ENTER: View{} | ESC: Back", open), left, screen_height() - 30.0, 16, GRAY);
+    }
+
+    /// Draw the frame enclosing the sigil: the main circle or a polygon
+    fn draw_frame(&self) {
+        let center = self.get_center();
+        match self.frame {
+            Frame::Circle => draw_circle_lines(center.x, center.y, CIRCLE_RADIUS, self.ring_width, self.ink(GRAY)),
+            Frame::Polygon(sides) => draw_poly_lines(center.x, center.y, sides, CIRCLE_RADIUS, -90.0, self.ring_width, self.ink(GRAY)),
+        }
+    }
+
+    /// Draw the input screen with text box, cursor, and selection
+    fn draw_input(&self) {
+        let center = self.get_center();
+        self.draw_frame();
+        // Instructions, lined up with the left of the text box
+        draw_text_ex(
+            "Enter your intention:",
+            self.input_text_origin().x,
+            center.y - 150.0,
+            TextParams {
+                font: self.font,
+                font_size: self.scaled_font(24),
+                color: self.ink(WHITE),
+                ..Default::default()
+            },
+        );
+        // Blinking cursor, shown in whichever field has focus
+        let cursor = if (self.blink_timer * 2.0) as i32 % 2 == 0 { "|" } else { " " };
+        let salt = if self.salt > 0 { self.salt.to_string() } else { String::new() };
+        draw_text_ex(
+            &format!("Salt: {}{}", salt, if self.salt_focused { cursor } else { "" }),
+            center.x + 110.0,
+            center.y - 150.0,
+            TextParams {
+                font: self.font,
+                font_size: 20,
+                color: self.ink(if self.salt_focused { YELLOW } else { GRAY }),
+                ..Default::default()
+            },
+        );
+        draw_text_ex(
+            if self.keep_vowels { "Vowels: kept" } else { "Vowels: removed" },
+            center.x + 110.0,
+            center.y - 128.0,
+            TextParams {
+                font: self.font,
+                font_size: 16,
+                color: self.ink(if self.keep_vowels { SKYBLUE } else { GRAY }),
+                ..Default::default()
+            },
+        );
+        draw_text_ex(
+            &format!("Numbers: {:?}", self.number_scheme),
+            center.x + 110.0,
+            center.y - 110.0,
+            TextParams {
+                font: self.font,
+                font_size: 16,
+                color: self.ink(if self.number_scheme == NumberScheme::SimpleMod10 { GRAY } else { SKYBLUE }),
+                ..Default::default()
+            },
+        );
+        let cursor = if self.salt_focused { " " } else { cursor };
+        // Text box position
+        let Vec2 { x: text_x, y: text_y } = self.input_text_origin();
+        if self.multiline {
+            self.draw_multiline_text(text_x, text_y, cursor);
+        } else {
+            // Only the part of the text scrolled into the box is drawn
+            let (shown_start, shown_end) = (self.input_shown_start(), self.input_visible_end());
+            // Draw selection background if any, cut to the part in the box
+            if let Some((start, end)) = self.selection_range() {
+                let (start, end) = (start.clamp(shown_start, shown_end), end.clamp(shown_start, shown_end));
+                let before_selection = &self.intention[shown_start..start];
+                let selection_text = &self.intention[start..end];
+                let before_width = measure_text(before_selection, Some(self.font), 20, 1.0).width;
+                let selection_width = measure_text(selection_text, Some(self.font), 20, 1.0).width;
+                draw_rectangle(
+                    text_x + before_width,
+                    text_y - 15.0,
+                    selection_width,
+                    25.0,
+                    self.ink(Color::from_rgba(100, 150, 255, 100)),
+                );
+            }
+            // Draw the text
+            draw_text_ex(
+                &self.intention[shown_start..shown_end],
+                text_x,
+                text_y,
+                TextParams {
+                    font: self.font,
+                    font_size: 20,
+                    color: self.ink(YELLOW),
+                    ..Default::default()
+                },
+            );
+            // Draw the cursor at the correct position
+            let cursor_x = text_x + measure_text(&self.intention[shown_start..self.cursor_pos], Some(self.font), 20, 1.0).width;
+            draw_text_ex(
+                cursor,
+                cursor_x,
+                text_y,
+                TextParams {
+                    font: self.font,
+                    font_size: 20,
+                    color: self.ink(YELLOW),
+                    ..Default::default()
+                },
+            );
+        }
+        self.draw_frozen_seed();
+        // Input instructions
+        let (instructions, options) = if self.multiline {
+            ("CTRL+ENTER when done | TAB: Salt | ESC: Back", "F6: Single line | F7: Vowels | F8: Numbers | F5: Freeze seed | F1: Help")
+        } else {
+            ("Press ENTER when done | TAB: Salt | ESC: Back", "F6: Multi-line | F7: Vowels | F8: Numbers | F5: Freeze seed | F1: Help")
+        };
+        self.draw_centered_text(instructions, center.y + 150.0, 18, self.ink(LIGHTGRAY));
+        self.draw_centered_text(options, center.y + 172.0, 18, self.ink(LIGHTGRAY));
+    }
+
+    /// Draw the intention wrapped over several lines, with selection and cursor on the right line
+    fn draw_multiline_text(&self, text_x: f32, text_y: f32, cursor: &str) {
+        let params = TextParams {
+            font: self.font,
+            font_size: 20,
+            color: self.ink(YELLOW),
+            ..Default::default()
+        };
+        let lines = self.wrapped_lines();
+        let cursor_line = Self::cursor_line(&lines, self.cursor_pos);
+        for (index, &(start, end)) in lines.iter().enumerate() {
+            let y = text_y + index as f32 * INPUT_LINE_HEIGHT;
+            // Highlight the part of the selection that falls on this line
+            if let Some((sel_start, sel_end)) = self.selection_range() {
+                let (from, to) = (sel_start.max(start), sel_end.min(end));
+                if from < to {
+                    let before_width = measure_text(&self.intention[start..from], Some(self.font), 20, 1.0).width;
+                    let selection_width = measure_text(&self.intention[from..to], Some(self.font), 20, 1.0).width;
+                    draw_rectangle(
+                        text_x + before_width,
+                        y - 15.0,
+                        selection_width,
+                        25.0,
+                        self.ink(Color::from_rgba(100, 150, 255, 100)),
+                    );
+                }
+            }
+            draw_text_ex(&self.intention[start..end], text_x, y, params);
+            if index == cursor_line {
+                let cursor_x = text_x + measure_text(&self.intention[start..self.cursor_pos], Some(self.font), 20, 1.0).width;
+                draw_text_ex(cursor, cursor_x, y, params);
+            }
+        }
+    }
+
+    /// Draw the sigil and its points, optionally animating the lines
+    fn draw_sigil(&self, animation: Option<(usize, f32)>) {
+        // The sigil is drawn zoomed, but the text over it stays at its normal size
+        set_camera(&self.zoom_camera());
+        self.draw_sigil_figure(animation);
+        set_default_camera();
+        if self.points.is_empty() {
+            return;
+        }
+        self.draw_frozen_seed();
+        self.draw_sigil_source();
+        // Show the attached note, if any
+        if !self.note.is_empty() && !matches!(self.state, State::Note { .. }) {
+            draw_text_ex(
+                &format!("Note: {}", self.note),
+                20.0,
+                55.0,
+                TextParams {
+                    font: self.font,
+                    font_size: 16,
+                    color: self.ink(LIGHTGRAY),
+                    ..Default::default()
+                },
+            );
+        }
+        if self.locked {
+            self.draw_lock_indicator();
+        }
+        if self.transparent_export() && matches!(self.state, State::Display) {
+            let text = "TRANSPARENT EXPORT";
+            let size = measure_text(text, Some(self.font), 16, 1.0);
+            draw_text_ex(
+                text,
+                screen_width() - size.width - 20.0,
+                80.0,
+                TextParams {
+                    font: self.font,
+                    font_size: 16,
+                    color: self.ink(GRAY),
+                    ..Default::default()
+                },
+            );
+        }
+        if self.showing_previous {
+            let text = "PREVIOUS SIGIL";
+            let size = measure_text(text, Some(self.font), 16, 1.0);
+            draw_text_ex(
+                text,
+                screen_width() - size.width - 20.0,
+                55.0,
+                TextParams {
+                    font: self.font,
+                    font_size: 16,
+                    color: self.ink(SKYBLUE),
+                    ..Default::default()
+                },
+            );
+        }
+        if self.focus_locked {
+            self.draw_focus_hint();
+            return;
+        }
+        // Show the controls along the bottom: the animation's while animating, or the sigil screen's
+        let hints = if animation.is_some() {
+            let pause = if matches!(self.state, State::Animating { paused: true, .. }) { "PAUSED | SPACE: Resume" } else { "SPACE: Pause" };
+            Some(format!(
+                "Speed: {:.1}x (+/- or scroll to adjust) | {} | LEFT/RIGHT: Step | ESC: Stop | F: Focus lock",
+                self.animation_speed, pause
+            ))
+        } else if matches!(self.state, State::Display) {
+            Some("H: Help | ESC: Back | SPACE: Animate | B: Breathe | SHIFT+B: Transparent export | +/-: Speed | WHEEL or CTRL +/-: Zoom | G: Regenerate | SHIFT+G: Gallery | A: Re-angle | SHIFT+A: Angle mode | R: Reset | L: Lock | S: Save as | SHIFT+S: Save all sizes | E: Note | P: Power number | V: Vowels | N: Node order | J: Close loop | U: Curves | D: Shadow | SHIFT+D: Glow | TAB: Node colors | Y: Line colors | Z: Size by value | W: Sketchy | O: Spokes | T: Theme | I: Invert | X: Export size | M: Animated SVG | SHIFT+M: Animated GIF | K: Copy code | C: Copy image | Q (hold): Previous sigil | F: Focus lock | DRAG: Move node (SHIFT snaps) | [ ]: Ring".to_string())
+        } else {
+            None
+        };
+        let hint_top = match hints {
+            Some(hints) => self.draw_hint_lines(&hints, 16, self.ink(LIGHTGRAY)),
+            None => screen_height() - 30.0,
+        };
+        if matches!(self.state, State::Display) {
+            self.draw_mouse_readout(hint_top - 25.0);
+        }
+        if self.status_timer > 0.0 {
+            draw_text_ex(
+                &self.status_message,
+                UI_MARGIN,
+                hint_top - 25.0,
+                TextParams {
+                    font: self.font,
+                    font_size: self.scaled_font(16),
+                    color: self.ink(Color { a: self.status_timer.min(1.0), ..SKYBLUE }),
+                    ..Default::default()
+                },
+            );
+        }
+    }
+
+    /// Draw the sigil itself, from its frame to its nodes
+    fn draw_sigil_figure(&self, animation: Option<(usize, f32)>) {
+        self.draw_frame();
+        if self.points.is_empty() {
+            return;
+        }
+        // Show the snap grid while a node is being dragged with snapping on
+        if self.dragging.is_some() && Self::shift_down() {
+            self.draw_snap_grid();
+        }
+        // Draw the decorative vowel points behind the sigil
+        for point in &self.ghost_points {
+            let pos = self.get_absolute_pos(point);
+            draw_circle(pos.x, pos.y, GHOST_RADIUS, self.ink(GHOST_COLOR));
+        }
+        // Draw the construction lines from the center out to each node shown so far
+        if self.spokes {
+            let center = self.get_center();
+            for (i, point) in self.points.iter().enumerate() {
+                if self.node_scale(i, animation) > 0.0 {
+                    let pos = self.get_absolute_pos(point);
+                    draw_line(center.x, center.y, pos.x, pos.y, 1.0, self.ink(SPOKE_COLOR));
                 }
-                self.selection_start = None;
             }
         }
+        // Draw the shadow underneath everything else
+        if self.drop_shadow {
+            self.draw_sigil_shadow(animation);
+        }
+        // Draw the lines and points in the chosen order
+        if self.nodes_behind_lines {
+            self.draw_sigil_nodes(animation);
+            self.draw_sigil_lines(animation);
+        } else {
+            self.draw_sigil_lines(animation);
+            self.draw_sigil_nodes(animation);
+        }
+        if self.debug_overlay {
+            self.draw_debug_overlay();
+        }
+    }
 
-        // Handle Home/End keys
-        if is_key_pressed(KeyCode::Home) {
-            if is_key_down(KeyCode::LeftShift) || is_key_down(KeyCode::RightShift) {
-                if self.selection_start.is_none() {
-                    self.selection_start = Some(self.cursor_pos);
+    /// Draw the grid dragged nodes snap to, covering the main circle
+    fn draw_snap_grid(&self) {
+        let center = self.get_center();
+        let steps = (CIRCLE_RADIUS / GRID_SPACING).ceil() as i32;
+        let extent = steps as f32 * GRID_SPACING;
+        match self.snap_grid {
+            SnapGrid::Cartesian => {
+                for i in -steps..=steps {
+                    let offset = i as f32 * GRID_SPACING;
+                    draw_line(center.x + offset, center.y - extent, center.x + offset, center.y + extent, 1.0, self.ink(GRID_COLOR));
+                    draw_line(center.x - extent, center.y + offset, center.x + extent, center.y + offset, 1.0, self.ink(GRID_COLOR));
                 }
-            } else {
-                self.selection_start = None;
             }
-            self.cursor_pos = 0;
-        }
-        if is_key_pressed(KeyCode::End) {
-            if is_key_down(KeyCode::LeftShift) || is_key_down(KeyCode::RightShift) {
-                if self.selection_start.is_none() {
-                    self.selection_start = Some(self.cursor_pos);
+            SnapGrid::Polar => {
+                for ring in 1..=steps {
+                    draw_circle_lines(center.x, center.y, ring as f32 * GRID_SPACING, 1.0, self.ink(GRID_COLOR));
+                }
+                let spokes = (2.0 * PI / POLAR_ANGLE_STEP).round() as i32;
+                for spoke in 0..spokes {
+                    let end = center + Vec2::from_angle(spoke as f32 * POLAR_ANGLE_STEP) * extent;
+                    draw_line(center.x, center.y, end.x, end.y, 1.0, self.ink(GRID_COLOR));
                 }
-            } else {
-                self.selection_start = None;
             }
-            self.cursor_pos = self.intention.len();
         }
+    }
 
-        // Handle Ctrl+A (Select All)
-        if is_key_pressed(KeyCode::A) && Self::ctrl_down() {
-            self.selection_start = Some(0);
-            self.cursor_pos = self.intention.len();
+    /// Draw the sigil's lines, optionally animating the current one
+    fn draw_sigil_lines(&self, animation: Option<(usize, f32)>) {
+        let center = self.get_center();
+        for (i, line) in self.visible_segments(animation).into_iter().enumerate() {
+            let stroke = self.stroke_points(i, &line);
+            let color = self.ink(self.segment_color(i));
+            // Build the glow up from the widest, faintest stroke, under the solid line
+            let glow_passes = if self.glow { GLOW_PASSES } else { 0 };
+            for pass in (1..=glow_passes).rev() {
+                let width = self.line_width + pass as f32 * GLOW_SPREAD;
+                for pair in stroke.windows(2) {
+                    let (start_pos, end_pos) = (center + pair[0], center + pair[1]);
+                    draw_line(start_pos.x, start_pos.y, end_pos.x, end_pos.y, width, Color { a: color.a * GLOW_ALPHA, ..color });
+                }
+            }
+            for pair in stroke.windows(2) {
+                let (start_pos, end_pos) = (center + pair[0], center + pair[1]);
+                draw_line(start_pos.x, start_pos.y, end_pos.x, end_pos.y, self.line_width, color);
+            }
         }
+    }
 
-        // Handle Ctrl+C (Copy) - prints to console for now
-        if is_key_pressed(KeyCode::C) && Self::ctrl_down() {
-            if let Some((start, end)) = self.selection_range() {
-                let selected_text = &self.intention[start..end];
-                println!("Copied: {}", selected_text);
+    /// Draw the sigil's drop shadow, offset down and right of the real path
+    fn draw_sigil_shadow(&self, animation: Option<(usize, f32)>) {
+        let origin = self.get_center() + vec2(self.shadow_offset, self.shadow_offset);
+        for (i, line) in self.visible_segments(animation).into_iter().enumerate() {
+            for pair in self.stroke_points(i, &line).windows(2) {
+                let (start_pos, end_pos) = (origin + pair[0], origin + pair[1]);
+                draw_line(start_pos.x, start_pos.y, end_pos.x, end_pos.y, self.line_width, self.ink(self.shadow_color));
             }
         }
+        let emphasized = self.emphasized_number();
+        for (i, point) in self.points.iter().enumerate() {
+            let pos = origin + point.relative_pos;
+            let radius = self.node_radius(point, emphasized) * self.node_scale(i, animation);
+            draw_circle(pos.x, pos.y, radius, self.ink(self.shadow_color));
+        }
+    }
 
-        // Handle Ctrl+V (Paste) - inserts placeholder text for now
-        if is_key_pressed(KeyCode::V) && Self::ctrl_down() {
-            let paste_text = "pasted_text"; // Placeholder for clipboard
-            if self.intention.len() + paste_text.len() <= 100 {
-                self.delete_selection();
-                for ch in paste_text.chars() {
-                    if ch.is_ascii_alphanumeric() || ch == ' ' {
-                        self.intention.insert(self.cursor_pos, ch);
-                        self.cursor_pos += 1;
-                    }
-                }
+    /// Draw the sigil's points with their numbers
+    fn draw_sigil_nodes(&self, animation: Option<(usize, f32)>) {
+        let emphasized = self.emphasized_number();
+        for (i, point) in self.points.iter().enumerate() {
+            let scale = self.node_scale(i, animation);
+            if scale <= 0.0 {
+                continue;
+            }
+            let pos = self.get_absolute_pos(point);
+            // Nodes sharing the power number are drawn larger with a gold ring
+            let radius = self.node_radius(point, emphasized) * scale;
+            draw_circle(pos.x, pos.y, radius, self.ink(self.node_color(i)));
+            if emphasized == Some(point.number) {
+                draw_circle_lines(pos.x, pos.y, radius, 2.0, self.ink(GOLD));
+            }
+            // Draw the number inside the circle, scaled with the node (once it's big enough to hold it)
+            if scale < 0.6 {
+                continue;
             }
+            let font_size = ((radius * 1.6) as u16).max(10);
+            let number_text = point.number.to_string();
+            let text_size = measure_text(&number_text, Some(self.font), font_size, 1.0);
+            draw_text_ex(
+                &number_text,
+                pos.x - text_size.width / 2.0,
+                pos.y + text_size.height / 2.0,
+                TextParams {
+                    font: self.font,
+                    font_size,
+                    color: self.ink(BLACK),
+                    ..Default::default()
+                },
+            );
         }
+    }
 
-        // Handle Ctrl+X (Cut) - prints to console for now
-        if is_key_pressed(KeyCode::X) && Self::ctrl_down() {
-            if let Some((start, end)) = self.selection_range() {
-                let selected_text = &self.intention[start..end];
-                println!("Cut: {}", selected_text);
-                self.intention.drain(start..end);
-                self.cursor_pos = start;
-                self.selection_start = None;
-            }
+    /// Draw the debug overlay: center crosshair, radius, and each node's index and position
+    fn draw_debug_overlay(&self) {
+        let center = self.get_center();
+        let color = Color::new(1.0, 0.3, 0.3, 0.8);
+        draw_line(center.x - 10.0, center.y, center.x + 10.0, center.y, 1.0, self.ink(color));
+        draw_line(center.x, center.y - 10.0, center.x, center.y + 10.0, 1.0, self.ink(color));
+        draw_line(center.x, center.y, center.x + CIRCLE_RADIUS, center.y, 1.0, self.ink(color));
+        draw_text_ex(
+            &format!("r = {}", CIRCLE_RADIUS),
+            center.x + CIRCLE_RADIUS / 2.0 - 20.0,
+            center.y - 6.0,
+            TextParams {
+                font: self.font,
+                font_size: 14,
+                color,
+                ..Default::default()
+            },
+        );
+        for (i, point) in self.points.iter().enumerate() {
+            let pos = self.get_absolute_pos(point);
+            draw_text_ex(
+                &format!("#{} ({:.1}, {:.1})", i, point.relative_pos.x, point.relative_pos.y),
+                pos.x + 14.0,
+                pos.y - 12.0,
+                TextParams {
+                    font: self.font,
+                    font_size: 14,
+                    color,
+                    ..Default::default()
+                },
+            );
         }
     }
 
-    /// Update the application state each frame
-    fn update(&mut self) {
-        self.blink_timer += get_frame_time();
+    /// Draw the intention and the numbers it became, in order, across the top of the screen,
+    /// so it's clear what a sigil came from when coming back to it (or in a screenshot)
+    fn draw_sigil_source(&self) {
+        // Keep clear of the indicators in the corners
+        let max_width = (screen_width() - 480.0).max(120.0);
+        let numbers: Vec<String> = self.points.iter().map(|point| point.number.to_string()).collect();
+        let mut lines = vec![self.fit_text(&numbers.join(" "), 14, max_width)];
+        // The intention isn't kept for the previous sigil, only its points
+        if !self.showing_previous {
+            let intention = self.intention.split_whitespace().collect::<Vec<_>>().join(" ");
+            lines.insert(0, self.fit_text(&intention, 14, max_width));
+        }
+        for (i, line) in lines.iter().enumerate() {
+            let size = measure_text(line, Some(self.font), 14, 1.0);
+            draw_text_ex(
+                line,
+                (screen_width() - size.width) / 2.0,
+                30.0 + i as f32 * 18.0,
+                TextParams {
+                    font: self.font,
+                    font_size: 14,
+                    color: self.ink(if i + 1 == lines.len() { GRAY } else { LIGHTGRAY }),
+                    ..Default::default()
+                },
+            );
+        }
+    }
 
-        // Handle save timer
-        if matches!(self.state, State::Saving) {
-            self.save_timer += get_frame_time();
-            if self.save_timer > 1.0 {
-                self.state = State::Display;
-                self.save_timer = 0.0;
+    /// Shorten text to fit within `max_width` at the given font size, ending it with "..."
+    /// if anything had to be cut
+    fn fit_text(&self, text: &str, font_size: u16, max_width: f32) -> String {
+        if measure_text(text, Some(self.font), font_size, 1.0).width <= max_width {
+            return text.to_string();
+        }
+        // Grow the text a character at a time until the next one would no longer fit
+        let mut fitted = "...".to_string();
+        for (index, ch) in text.char_indices() {
+            let candidate = format!("{}...", text[..index + ch.len_utf8()].trim_end());
+            if measure_text(&candidate, Some(self.font), font_size, 1.0).width > max_width {
+                break;
             }
+            fitted = candidate;
         }
+        fitted
+    }
 
-        // State machine for the app
-        match &mut self.state {
-            State::Start => {
-                // Consume any character input
-                while get_char_pressed().is_some() {}
-                if is_key_pressed(KeyCode::Space) {
-                    self.state = State::Input;
-                }
-            }
-            State::Input => {
-                // Handle text input and editing
-                self.handle_text_input();
-                if is_key_pressed(KeyCode::Enter) && !self.intention.trim().is_empty() {
-                    self.generate_sigil();
-                }
-            }
-            State::Display => {
-                // Consume any character input
-                while get_char_pressed().is_some() {}
-                if is_key_pressed(KeyCode::Space) && self.points.len() > 1 {
-                    self.state = State::Animating { progress: 0.0, line: 0 };
-                } else if is_key_pressed(KeyCode::R) {
-                    self.reset();
-                } else if is_key_pressed(KeyCode::S) {
-                    if let Err(e) = self.save_sigil() {
-                        eprintln!("Failed to save sigil: {}", e);
-                    }
-                    self.state = State::Saving;
-                }
-            }
-            State::Animating { progress, line } => {
-                // Consume any character input
-                while get_char_pressed().is_some() {}
-                // Animate the drawing of the sigil
-                *progress += get_frame_time() * ANIMATION_SPEED;
-                if *progress >= 1.0 {
-                    *progress = 0.0;
-                    *line += 1;
-                    if *line >= self.points.len() - 1 {
-                        self.state = State::Display;
-                    }
-                }
-            }
-            State::Saving => {
-                // Consume any character input
-                while get_char_pressed().is_some() {}
+    /// Shorten text to fit within `max_width` like `fit_text`, but keeping its end and
+    /// starting it with "..." instead
+    fn fit_text_end(&self, text: &str, font_size: u16, max_width: f32) -> String {
+        if measure_text(text, Some(self.font), font_size, 1.0).width <= max_width {
+            return text.to_string();
+        }
+        let mut fitted = "...".to_string();
+        for (index, _) in text.char_indices().rev() {
+            let candidate = format!("...{}", &text[index..]);
+            if measure_text(&candidate, Some(self.font), font_size, 1.0).width > max_width {
+                break;
             }
+            fitted = candidate;
         }
+        fitted
     }
 
-    /// Reset the app to the input state
-    fn reset(&mut self) {
-        self.state = State::Input;
-        self.intention.clear();
-        self.points.clear();
-        self.blink_timer = 0.0;
-        self.cursor_pos = 0;
-        self.selection_start = None;
+    /// Draw the frozen session seed in the top-left corner, if the RNG is frozen
+    fn draw_frozen_seed(&self) {
+        if let Some(seed) = self.frozen_seed {
+            draw_text_ex(
+                &format!("Frozen seed: {} (F5 to unfreeze)", seed),
+                20.0,
+                30.0,
+                TextParams {
+                    font: self.font,
+                    font_size: 16,
+                    color: self.ink(SKYBLUE),
+                    ..Default::default()
+                },
+            );
+        }
     }
 
-    /// Draw the current frame
-    fn draw(&self) {
-        clear_background(Color::from_rgba(10, 5, 20, 255));
-        match &self.state {
-            State::Start => self.draw_start(),
-            State::Input => self.draw_input(),
-            State::Display => self.draw_sigil(None),
-            State::Animating { progress, line } => self.draw_sigil(Some((*line, *progress))),
-            State::Saving => {
-                self.draw_sigil(None);
-                self.draw_saving_message();
-            }
+    /// Draw the faint hint for leaving focus lock, filling in while Escape is held
+    fn draw_focus_hint(&self) {
+        let text = "Hold ESC to exit focus";
+        let size = measure_text(text, Some(self.font), 16, 1.0);
+        let x = (screen_width() - size.width) / 2.0;
+        let y = screen_height() - 30.0;
+        let alpha = 0.3 + 0.7 * self.unlock_hold / FOCUS_UNLOCK_HOLD;
+        draw_text_ex(
+            text,
+            x,
+            y,
+            TextParams {
+                font: self.font,
+                font_size: 16,
+                color: self.ink(Color::new(0.8, 0.8, 0.8, alpha)),
+                ..Default::default()
+            },
+        );
+        if self.unlock_hold > 0.0 {
+            let progress = self.unlock_hold / FOCUS_UNLOCK_HOLD;
+            draw_line(x, y + 8.0, x + size.width * progress, y + 8.0, 2.0, self.ink(Color::new(0.8, 0.8, 0.8, alpha)));
         }
     }
 
-    /// Draw the start screen
-    fn draw_start(&self) {
-        let center = self.get_center();
+    /// Draw the mouse position relative to the circle's center (matching `relative_pos`)
+    /// at the right-hand side of the window, on the line at `y`
+    fn draw_mouse_readout(&self, y: f32) {
+        let (mouse_x, mouse_y) = mouse_position();
+        let relative = self.screen_to_relative(vec2(mouse_x, mouse_y));
+        let text = format!("Mouse: ({:.1}, {:.1})", relative.x, relative.y);
+        let font_size = self.scaled_font(16);
+        let size = measure_text(&text, Some(self.font), font_size, 1.0);
         draw_text_ex(
-            "SIGIL GENERATOR",
-            center.x - 200.0,
-            center.y - 50.0,
+            &text,
+            screen_width() - size.width - UI_MARGIN,
+            y,
             TextParams {
-                font_size: 32,
-                color: WHITE,
+                font: self.font,
+                font_size,
+                color: self.ink(LIGHTGRAY),
                 ..Default::default()
             },
         );
+    }
+
+    /// Draw a small indicator in the top-right corner while the layout is locked
+    fn draw_lock_indicator(&self) {
+        let text = "LOCKED";
+        let size = measure_text(text, Some(self.font), 16, 1.0);
         draw_text_ex(
-            "Press SPACE to begin",
-            center.x - 120.0,
-            center.y + 20.0,
+            text,
+            screen_width() - size.width - 20.0,
+            30.0,
             TextParams {
-                font_size: 24,
-                color: LIGHTGRAY,
+                font: self.font,
+                font_size: 16,
+                color: self.ink(GOLD),
                 ..Default::default()
             },
         );
     }
 
-    /// Draw the input screen with text box, cursor, and selection
-    fn draw_input(&self) {
+    /// Draw the note entry box over the sigil
+    fn draw_note_entry(&self) {
+        let y = screen_height() - 110.0;
+        draw_rectangle(10.0, y, screen_width() - 20.0, 90.0, self.ink(Color::from_rgba(0, 0, 0, 200)));
+        let cursor = if (self.blink_timer * 2.0) as i32 % 2 == 0 { "|" } else { "" };
+        draw_text_ex(
+            &format!("Note: {}{}", self.note, cursor),
+            20.0,
+            y + 35.0,
+            TextParams {
+                font: self.font,
+                font_size: 18,
+                color: self.ink(YELLOW),
+                ..Default::default()
+            },
+        );
+        draw_text_ex(
+            "ENTER: Done | ESC: Cancel",
+            20.0,
+            y + 70.0,
+            TextParams {
+                font: self.font,
+                font_size: 16,
+                color: self.ink(LIGHTGRAY),
+                ..Default::default()
+            },
+        );
+    }
+
+    /// Draw the save format menu over the sigil
+    fn draw_format_menu(&self) {
         let center = self.get_center();
-        // Draw the main circle
-        draw_circle_lines(center.x, center.y, CIRCLE_RADIUS, 3.0, GRAY);
-        // Instructions
+        draw_rectangle(
+            center.x - 150.0,
+            center.y - 70.0,
+            300.0,
+            140.0,
+            self.ink(Color::from_rgba(0, 0, 0, 200)),
+        );
         draw_text_ex(
-            "Enter your intention:",
+            "Save as:",
+            center.x - 130.0,
+            center.y - 40.0,
+            TextParams {
+                font: self.font,
+                font_size: 24,
+                color: self.ink(WHITE),
+                ..Default::default()
+            },
+        );
+        for (i, option) in ["P: PNG", "S: SVG (animated)", "J: JPEG", "G: GIF"].iter().enumerate() {
+            draw_text_ex(
+                option,
+                center.x - 130.0 + (i % 2) as f32 * 140.0,
+                center.y + (i / 2) as f32 * 30.0,
+                TextParams {
+                    font: self.font,
+                    font_size: 20,
+                    color: self.ink(YELLOW),
+                    ..Default::default()
+                },
+            );
+        }
+        draw_text_ex(
+            "ESC: Cancel",
+            center.x - 130.0,
+            center.y + 55.0,
+            TextParams {
+                font: self.font,
+                font_size: 16,
+                color: self.ink(LIGHTGRAY),
+                ..Default::default()
+            },
+        );
+    }
+
+    /// Draw the question asked before discarding the sigil
+    fn draw_reset_prompt(&self) {
+        let center = self.get_center();
+        draw_rectangle(
             center.x - 150.0,
-            center.y - 150.0,
+            center.y - 50.0,
+            300.0,
+            100.0,
+            self.ink(Color::from_rgba(0, 0, 0, 200)),
+        );
+        draw_text_ex(
+            "Discard this sigil?",
+            center.x - 130.0,
+            center.y - 10.0,
             TextParams {
+                font: self.font,
                 font_size: 24,
-                color: WHITE,
+                color: self.ink(WHITE),
                 ..Default::default()
             },
         );
-        // Blinking cursor
-        let cursor = if (self.blink_timer * 2.0) as i32 % 2 == 0 { "|" } else { " " };
-        // Text box position
-        let text_x = center.x - 200.0;
-        let text_y = center.y - 100.0;
-        // Draw selection background if any
-        if let Some(selection_start) = self.selection_start {
-            let (start, end) = if selection_start < self.cursor_pos {
-                (selection_start, self.cursor_pos)
-            } else {
-                (self.cursor_pos, selection_start)
-            };
-            let before_selection = &self.intention[..start];
-            let selection_text = &self.intention[start..end];
-            let before_width = measure_text(before_selection, None, 20, 1.0).width;
-            let selection_width = measure_text(selection_text, None, 20, 1.0).width;
-            draw_rectangle(
-                text_x + before_width,
-                text_y - 15.0,
-                selection_width,
-                25.0,
-                Color::from_rgba(100, 150, 255, 100),
-            );
-        }
-        // Draw the text
         draw_text_ex(
-            &self.intention,
-            text_x,
-            text_y,
+            "Y: Discard | N: Keep",
+            center.x - 130.0,
+            center.y + 25.0,
             TextParams {
+                font: self.font,
                 font_size: 20,
-                color: YELLOW,
+                color: self.ink(YELLOW),
                 ..Default::default()
             },
         );
-        // Draw the cursor at the correct position
-        let cursor_x = text_x + measure_text(&self.intention[..self.cursor_pos], None, 20, 1.0).width;
+    }
+
+    /// Draw the progress of an all-sizes export, with `done` of the sizes written
+    fn draw_export_progress(&self, done: usize) {
+        let center = self.get_center();
+        let total = EXPORT_VARIANT_SIZES.len();
+        draw_rectangle(
+            center.x - 150.0,
+            center.y - 50.0,
+            300.0,
+            100.0,
+            self.ink(Color::from_rgba(0, 0, 0, 200)),
+        );
         draw_text_ex(
-            cursor,
-            cursor_x,
-            text_y,
+            &format!("Saving {}px ({} of {})", EXPORT_VARIANT_SIZES[done.min(total - 1)], done + 1, total),
+            center.x - 130.0,
+            center.y - 15.0,
             TextParams {
+                font: self.font,
                 font_size: 20,
-                color: YELLOW,
+                color: self.ink(WHITE),
                 ..Default::default()
             },
         );
-        // Input instructions
+        // Progress bar
+        draw_rectangle(center.x - 130.0, center.y, 260.0, 10.0, self.ink(DARKGRAY));
+        draw_rectangle(center.x - 130.0, center.y, 260.0 * done as f32 / total as f32, 10.0, self.ink(GREEN));
         draw_text_ex(
-            "Press ENTER when done",
-            center.x - 120.0,
-            center.y + 150.0,
+            "ESC: Cancel (keeps sizes already saved)",
+            center.x - 130.0,
+            center.y + 35.0,
             TextParams {
-                font_size: 18,
-                color: LIGHTGRAY,
+                font: self.font,
+                font_size: 16,
+                color: self.ink(LIGHTGRAY),
                 ..Default::default()
             },
         );
     }
 
-    /// Draw the sigil and its points, optionally animating the lines
-    fn draw_sigil(&self, animation: Option<(usize, f32)>) {
+    /// Draw the 'Sigil Saved!' message overlay, easing out over its last moments
+    fn draw_saving_message(&self) {
         let center = self.get_center();
-        // Draw the main circle
-        draw_circle_lines(center.x, center.y, CIRCLE_RADIUS, 3.0, GRAY);
-        if self.points.is_empty() {
-            return;
-        }
-        // Draw completed lines
-        let completed_lines = match animation {
-            Some((current_line, _)) => current_line,
-            None => self.points.len() - 1,
-        };
-        for i in 0..completed_lines {
-            if i + 1 < self.points.len() {
-                let start_pos = self.get_absolute_pos(&self.points[i]);
-                let end_pos = self.get_absolute_pos(&self.points[i + 1]);
-                draw_line(
-                    start_pos.x,
-                    start_pos.y,
-                    end_pos.x,
-                    end_pos.y,
-                    3.0,
-                    SKYBLUE,
-                );
-            }
-        }
-        // Draw the currently animating line
-        if let Some((current_line, progress)) = animation {
-            if current_line + 1 < self.points.len() {
-                let start_pos = self.get_absolute_pos(&self.points[current_line]);
-                let end_pos = self.get_absolute_pos(&self.points[current_line + 1]);
-                let current_pos = start_pos + (end_pos - start_pos) * progress;
-                draw_line(start_pos.x, start_pos.y, current_pos.x, current_pos.y, 3.0, SKYBLUE);
-            }
-        }
-        // Draw the points with numbers
-        for (i, point) in self.points.iter().enumerate() {
-            let pos = self.get_absolute_pos(point);
-            let color = if i == 0 {
-                GREEN
-            } else if i == self.points.len() - 1 {
-                RED
-            } else {
-                ORANGE
+        let remaining = self.save_message_time() - self.save_timer;
+        let fade = (remaining / SAVE_MESSAGE_FADE).clamp(0.0, 1.0);
+        let alpha = fade * fade * (3.0 - 2.0 * fade);
+        let (title, detail, color) = match &self.last_save_result {
+            Some(Err(e)) => ("Save Failed", e.as_str(), RED),
+            Some(Ok(path)) => ("Sigil Saved!", path.as_str(), GREEN),
+            None => ("Sigil Saved!", "", GREEN),
+        };
+        // Draw a semi-transparent background, wide enough for the path or error
+        let width = (screen_width() - 2.0 * UI_MARGIN).min(500.0);
+        draw_rectangle(
+            center.x - width / 2.0,
+            center.y - 50.0,
+            width,
+            100.0,
+            self.ink(Color::new(0.0, 0.0, 0.0, 200.0 / 255.0 * alpha)),
+        );
+        // Draw the message, with where the sigil went (or what went wrong) underneath
+        for (text, font_size, y) in [(title, 24, center.y - 10.0), (detail, 16, center.y + 20.0)] {
+            // A long path is cut from the front, keeping the file name in view
+            let text = match &self.last_save_result {
+                Some(Ok(_)) if text == detail => self.fit_text_end(text, font_size, width - 20.0),
+                _ => self.fit_text(text, font_size, width - 20.0),
             };
-            draw_circle(pos.x, pos.y, 10.0, color);
-            // Draw the number inside the circle
-            let number_text = point.number.to_string();
-            let text_size = measure_text(&number_text, None, 16, 1.0);
+            let size = measure_text(&text, Some(self.font), font_size, 1.0);
             draw_text_ex(
-                &number_text,
-                pos.x - text_size.width / 2.0,
-                pos.y + text_size.height / 2.0,
+                &text,
+                center.x - size.width / 2.0,
+                y,
                 TextParams {
-                    font_size: 16,
-                    color: BLACK,
+                    font: self.font,
+                    font_size,
+                    color: self.ink(Color { a: alpha, ..color }),
                     ..Default::default()
                 },
             );
         }
-        // Display instructions at the bottom
-        if matches!(self.state, State::Display) {
-            draw_text_ex(
-                "SPACE: Animate | R: Reset | S: Save",
-                20.0,
-                screen_height() - 30.0,
-                TextParams {
-                    font_size: 16,
-                    color: LIGHTGRAY,
-                    ..Default::default()
-                },
-            );
+    }
+}
+
+/// Advance the animation by `delta` segments' worth of progress. Any overshoot past the
+/// end of a segment carries into the next one, so a long frame can finish several lines
+/// and the finished ones are always drawn complete. Returns the new (progress, line),
+/// or None once all `segment_count` segments are drawn.
+fn advance_animation(progress: f32, line: usize, delta: f32, segment_count: usize) -> Option<(f32, usize)> {
+    let mut progress = progress.max(0.0) + delta.max(0.0);
+    let mut line = line;
+    while progress >= 1.0 {
+        progress -= 1.0;
+        line += 1;
+    }
+    if line >= segment_count {
+        None
+    } else {
+        Some((progress.clamp(0.0, 1.0), line))
+    }
+}
+
+/// Run the animation backwards by `delta` segments' worth of progress, un-drawing from
+/// the end of the path. Returns the new (progress, line), or None once nothing is left.
+fn rewind_animation(progress: f32, line: usize, delta: f32) -> Option<(f32, usize)> {
+    let mut progress = progress.min(1.0) - delta.max(0.0);
+    let mut line = line;
+    while progress < 0.0 {
+        if line == 0 {
+            return None;
         }
+        progress += 1.0;
+        line -= 1;
     }
+    Some((progress, line))
+}
 
-    /// Draw the 'Sigil Saved!' message overlay
-    fn draw_saving_message(&self) {
-        let center = self.get_center();
-        // Draw a semi-transparent background
-        draw_rectangle(
-            center.x - 150.0,
-            center.y - 50.0,
-            300.0,
-            100.0,
-            Color::from_rgba(0, 0, 0, 200),
-        );
-        // Draw the message
-        draw_text_ex(
-            "Sigil Saved!",
-            center.x - 60.0,
-            center.y - 10.0,
-            TextParams {
-                font_size: 24,
-                color: GREEN,
-                ..Default::default()
-            },
-        );
+/// Drop any point that would form a segment shorter than `min_len` with the
+/// previously kept point, merging the short segment into its start point
+fn merge_short_segments(points: &mut Vec<SigilPoint>, min_len: f32) {
+    if min_len <= 0.0 {
+        return;
+    }
+    let mut last: Option<Vec2> = None;
+    points.retain(|point| {
+        let kept = last.is_none_or(|last| last.distance(point.relative_pos) >= min_len);
+        if kept {
+            last = Some(point.relative_pos);
+        }
+        kept
+    });
+}
+
+/// Nudge angles apart until every pair of neighbors around the circle is at least
+/// `min_sep` radians apart, pushing each close pair away from each other equally.
+/// The separation is capped at half an even share of the circle so it can always be met
+fn enforce_min_spacing(angles: &mut [f32], min_sep: f32) {
+    let count = angles.len();
+    if count < 2 {
+        return;
+    }
+    let min_sep = min_sep.min(PI / count as f32);
+    let mut order: Vec<usize> = (0..count).collect();
+    for _ in 0..SPACING_PASSES {
+        for angle in angles.iter_mut() {
+            *angle = angle.rem_euclid(2.0 * PI);
+        }
+        order.sort_by(|&a, &b| angles[a].total_cmp(&angles[b]));
+        let mut moved = false;
+        for k in 0..count {
+            let (a, b) = (order[k], order[(k + 1) % count]);
+            // The last point's neighbor is the first, once around the circle
+            let gap = if k + 1 == count { angles[b] + 2.0 * PI - angles[a] } else { angles[b] - angles[a] };
+            if gap < min_sep * 0.999 {
+                let push = (min_sep - gap) / 2.0;
+                angles[a] -= push;
+                angles[b] += push;
+                moved = true;
+            }
+        }
+        if !moved {
+            break;
+        }
+    }
+}
+
+/// Snap a position relative to the circle's center to the nearest grid point
+fn snap_to_grid(pos: Vec2, grid: SnapGrid) -> Vec2 {
+    match grid {
+        SnapGrid::Cartesian => (pos / GRID_SPACING).round() * GRID_SPACING,
+        SnapGrid::Polar => {
+            let radius = (pos.length() / GRID_SPACING).round() * GRID_SPACING;
+            let angle = (pos.y.atan2(pos.x) / POLAR_ANGLE_STEP).round() * POLAR_ANGLE_STEP;
+            Vec2::from_angle(angle) * radius
+        }
+    }
+}
+
+/// Walk a run of characters, given as each one's index and width, and get the boundary
+/// closest to `x` pixels from the start: a character's own index until `x` passes its
+/// middle, or `end` once it's past them all
+fn boundary_at_x(widths: impl IntoIterator<Item = (usize, f32)>, end: usize, x: f32) -> usize {
+    let mut width = 0.0;
+    for (index, ch_width) in widths {
+        if x < width + ch_width / 2.0 {
+            return index;
+        }
+        width += ch_width;
+    }
+    end
+}
+
+/// Get the characters of an intention that become numbers: lowercased with accents
+/// stripped, with vowels (unless they're kept) and repeated characters removed, in
+/// the order they appear
+fn filter_letters(intention: &str, keep_vowels: bool) -> String {
+    let vowels = if keep_vowels { "" } else { "aeiou" };
+    let mut seen = HashSet::new();
+    intention
+        .chars()
+        .filter_map(fold_to_ascii)
+        .filter(|c| !vowels.contains(*c))
+        .filter(|c| seen.insert(*c))
+        .collect()
+}
+
+/// Turn an intention into the numbers its letters stand for, in the order they're
+/// written, before any salt or shuffling: each letter left by `filter_letters`
+/// paired with its number under `scheme`
+fn intention_to_numbers(intention: &str, keep_vowels: bool, scheme: NumberScheme) -> Vec<(char, u8)> {
+    filter_letters(intention, keep_vowels).chars().map(|c| (c, scheme.number_for(c))).collect()
+}
+
+/// Merge each run of consecutive equal numbers into a single entry, keeping the
+/// character the run started with, so every segment joins two different numbers
+fn collapse_repeated_numbers(numbers: &mut Vec<u8>, sources: &mut Vec<char>) {
+    let mut pairs: Vec<(u8, char)> = numbers.iter().copied().zip(sources.iter().copied()).collect();
+    pairs.dedup_by_key(|(number, _)| *number);
+    (*numbers, *sources) = pairs.into_iter().unzip();
+}
+
+/// Check whether the window is minimized. Macroquad doesn't report focus or
+/// minimize events, but a minimized window has no drawable area on most platforms
+fn window_minimized() -> bool {
+    screen_width() < 1.0 || screen_height() < 1.0
+}
+
+/// Check whether a character can be part of an intention: a letter or digit in any
+/// script, or a space
+fn is_intention_char(ch: char) -> bool {
+    ch.is_alphanumeric() || ch == ' '
+}
+
+/// Pick a new seed for sigil generation from the current time
+fn fresh_seed() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_nanos() as u64)
+        .unwrap_or(0)
+}
+
+/// Get a repeatable pseudo-random value in -1..1 for position `index` of a
+/// sequence identified by `seed` (splitmix64 of the combined input)
+fn hash_noise(seed: u64, index: u64) -> f32 {
+    let mut z = seed.wrapping_add(index.wrapping_mul(0x9e3779b97f4a7c15));
+    z = (z ^ (z >> 30)).wrapping_mul(0xbf58476d1ce4e5b9);
+    z = (z ^ (z >> 27)).wrapping_mul(0x94d049bb133111eb);
+    z ^= z >> 31;
+    (z >> 40) as f32 / (1u64 << 23) as f32 - 1.0
+}
+
+/// Check whether an output path names a directory: one that already exists, or one
+/// written with a trailing separator
+fn is_dir_path(path: &Path) -> bool {
+    path.is_dir() || path.as_os_str().to_string_lossy().ends_with(['/', std::path::MAIN_SEPARATOR])
+}
+
+/// Get the absolute form of a path just written, so it can be found from anywhere,
+/// falling back to the path as given if it can't be resolved
+fn full_path(path: &str) -> String {
+    std::path::absolute(path).map_or_else(|_| path.to_string(), |full| full.display().to_string())
+}
+
+/// Get the value following a command-line flag, if present
+fn arg_value(args: &[String], flag: &str) -> Option<String> {
+    args.iter()
+        .position(|arg| arg == flag)
+        .and_then(|i| args.get(i + 1))
+        .cloned()
+}
+
+/// Get the filename for one size of an all-sizes export, suffixing the base name with the size
+fn size_variant_path(base: &Path, size: u16) -> PathBuf {
+    let stem = base.file_stem().unwrap_or_default().to_string_lossy();
+    base.with_file_name(format!("{}_{}.png", stem, size))
+}
+
+/// Get the filename for one of a batch of variants, suffixing the base name with `_v` and its number
+fn variant_path(base: &Path, number: usize) -> PathBuf {
+    let stem = base.file_stem().unwrap_or_default().to_string_lossy();
+    base.with_file_name(format!("{}_v{}.png", stem, number))
+}
+
+/// Blend a translucent color over an opaque background, giving an opaque color
+fn flatten_color(color: Color, background: Color) -> Color {
+    Color::new(
+        background.r + (color.r - background.r) * color.a,
+        background.g + (color.g - background.g) * color.a,
+        background.b + (color.b - background.b) * color.a,
+        1.0,
+    )
+}
+
+/// Blend every pixel of an image over an opaque background, making the image opaque
+fn flatten_image(image: &mut Image, background: Color) {
+    let background: [u8; 4] = background.into();
+    for pixel in image.bytes.chunks_mut(4) {
+        let alpha = pixel[3] as u32;
+        for (channel, &back) in pixel[..3].iter_mut().zip(&background) {
+            *channel = ((*channel as u32 * alpha + back as u32 * (255 - alpha)) / 255) as u8;
+        }
+        pixel[3] = 255;
+    }
+}
+
+/// Write an image to a PNG file with the given tEXt metadata entries.
+/// Rows are written top to bottom, matching how the sigil is drawn on screen.
+fn write_png(image: &Image, path: &Path, metadata: &[(&str, &str)]) -> std::io::Result<()> {
+    let file = std::io::BufWriter::new(std::fs::File::create(path)?);
+    let mut encoder = png::Encoder::new(file, image.width as u32, image.height as u32);
+    encoder.set_color(png::ColorType::Rgba);
+    encoder.set_depth(png::BitDepth::Eight);
+    for (keyword, text) in metadata {
+        encoder.add_text_chunk(keyword.to_string(), text.to_string())?;
+    }
+    let mut writer = encoder.write_header()?;
+    writer.write_image_data(&image.bytes)?;
+    Ok(())
+}
+
+/// Convert a linear color channel value (0-1) to its sRGB-encoded value
+fn linear_to_srgb(value: f32) -> f32 {
+    if value <= 0.0031308 {
+        value * 12.92
+    } else {
+        1.055 * value.powf(1.0 / 2.4) - 0.055
+    }
+}
+
+/// Gamma-encode every pixel of an image from linear to sRGB, leaving alpha alone
+fn encode_srgb(image: &mut Image) {
+    for pixel in image.bytes.chunks_mut(4) {
+        for channel in &mut pixel[..3] {
+            *channel = (linear_to_srgb(*channel as f32 / 255.0) * 255.0).round() as u8;
+        }
     }
 }
 
+/// Write an image to a JPEG file. JPEG has no alpha, so the image should be opaque
+fn write_jpeg(image: &Image, path: &Path) -> std::io::Result<()> {
+    let encoder = jpeg_encoder::Encoder::new_file(path, 90).map_err(std::io::Error::other)?;
+    encoder
+        .encode(&image.bytes, image.width, image.height, jpeg_encoder::ColorType::Rgba)
+        .map_err(std::io::Error::other)
+}
+
+/// Find the entry of an RGB palette closest to a color
+fn nearest_palette_index(palette: &[u8], rgb: [u8; 3]) -> u8 {
+    let distance = |entry: &[u8]| -> i32 {
+        entry.iter().zip(rgb).map(|(&a, b)| (a as i32 - b as i32).pow(2)).sum()
+    };
+    palette
+        .chunks(3)
+        .enumerate()
+        .min_by_key(|(_, entry)| distance(entry))
+        .map_or(0, |(index, _)| index as u8)
+}
+
+/// Write an image to a single-frame GIF file, quantizing it to a 256-color palette
+fn write_gif(image: &Image, path: &Path) -> std::io::Result<()> {
+    let file = std::io::BufWriter::new(std::fs::File::create(path)?);
+    let mut encoder = gif::Encoder::new(file, image.width, image.height, &[]).map_err(std::io::Error::other)?;
+    let mut bytes = image.bytes.clone();
+    let frame = gif::Frame::from_rgba_speed(image.width, image.height, &mut bytes, 10);
+    encoder.write_frame(&frame).map_err(std::io::Error::other)
+}
+
 // Helper functions for drawing lines and circles on Image
 fn draw_line_on_image(image: &mut macroquad::texture::Image, x0: u32, y0: u32, x1: u32, y1: u32, color: Color) {
     let (mut x0, mut y0, x1, y1) = (x0 as i32, y0 as i32, x1 as i32, y1 as i32);
@@ -670,6 +4550,94 @@ fn draw_line_on_image(image: &mut macroquad::texture::Image, x0: u32, y0: u32, x
         if e2 <= dx { err += dx; y0 += sy; }
     }
 }
+/// Draw a ring of the given thickness centered on (cx, cy), filling every pixel
+/// whose distance from the center falls within the stroke
+fn draw_ring_on_image(image: &mut macroquad::texture::Image, cx: f32, cy: f32, radius: f32, width: f32, color: Color) {
+    let inner = radius - width / 2.0;
+    let outer = radius + width / 2.0;
+    let w = image.width() as i32;
+    let h = image.height() as i32;
+    let x_min = ((cx - outer).floor() as i32).max(0);
+    let x_max = ((cx + outer).ceil() as i32).min(w - 1);
+    let y_min = ((cy - outer).floor() as i32).max(0);
+    let y_max = ((cy + outer).ceil() as i32).min(h - 1);
+    for y in y_min..=y_max {
+        for x in x_min..=x_max {
+            let dist = vec2(x as f32 - cx, y as f32 - cy).length();
+            if dist >= inner && dist <= outer {
+                image.set_pixel(x as u32, y as u32, color);
+            }
+        }
+    }
+}
+/// Draw a line of the given thickness by filling every pixel within half the
+/// thickness of the segment from `start` to `end`
+fn draw_thick_line_on_image(image: &mut macroquad::texture::Image, start: Vec2, end: Vec2, width: f32, color: Color) {
+    let half = width / 2.0;
+    let w = image.width() as i32;
+    let h = image.height() as i32;
+    let x_min = ((start.x.min(end.x) - half).floor() as i32).max(0);
+    let x_max = ((start.x.max(end.x) + half).ceil() as i32).min(w - 1);
+    let y_min = ((start.y.min(end.y) - half).floor() as i32).max(0);
+    let y_max = ((start.y.max(end.y) + half).ceil() as i32).min(h - 1);
+    let segment = end - start;
+    let length_squared = segment.length_squared().max(f32::EPSILON);
+    for y in y_min..=y_max {
+        for x in x_min..=x_max {
+            let p = vec2(x as f32, y as f32);
+            let t = ((p - start).dot(segment) / length_squared).clamp(0.0, 1.0);
+            if p.distance(start + segment * t) <= half {
+                image.set_pixel(x as u32, y as u32, color);
+            }
+        }
+    }
+}
+/// Draw an anti-aliased polyline of the given thickness with round joins. Each pixel is
+/// covered by how far it sits inside the stroke of the nearest piece, so pixels where
+/// pieces meet are only blended once
+fn draw_smooth_polyline_on_image(image: &mut Image, points: &[Vec2], width: f32, color: Color) {
+    let half = width / 2.0;
+    let w = image.width() as i32;
+    let h = image.height() as i32;
+    let mut coverage: HashMap<(u32, u32), f32> = HashMap::new();
+    for pair in points.windows(2) {
+        let (start, end) = (pair[0], pair[1]);
+        let x_min = ((start.x.min(end.x) - half - 1.0).floor() as i32).max(0);
+        let x_max = ((start.x.max(end.x) + half + 1.0).ceil() as i32).min(w - 1);
+        let y_min = ((start.y.min(end.y) - half - 1.0).floor() as i32).max(0);
+        let y_max = ((start.y.max(end.y) + half + 1.0).ceil() as i32).min(h - 1);
+        let segment = end - start;
+        let length_squared = segment.length_squared().max(f32::EPSILON);
+        for y in y_min..=y_max {
+            for x in x_min..=x_max {
+                let p = vec2(x as f32, y as f32);
+                let t = ((p - start).dot(segment) / length_squared).clamp(0.0, 1.0);
+                let amount = (half + 0.5 - p.distance(start + segment * t)).clamp(0.0, 1.0);
+                if amount > 0.0 {
+                    let entry = coverage.entry((x as u32, y as u32)).or_insert(0.0);
+                    *entry = entry.max(amount);
+                }
+            }
+        }
+    }
+    for ((x, y), amount) in coverage {
+        blend_pixel(image, x, y, color, amount);
+    }
+}
+
+/// Blend a color over a pixel, scaled by how much of the pixel it covers. Alpha is
+/// composited too, so soft edges stay soft over a transparent background
+fn blend_pixel(image: &mut Image, x: u32, y: u32, color: Color, coverage: f32) {
+    let below = image.get_pixel(x, y);
+    let alpha = color.a * coverage;
+    let out_alpha = alpha + below.a * (1.0 - alpha);
+    if out_alpha <= 0.0 {
+        return;
+    }
+    let mix = |top: f32, bottom: f32| (top * alpha + bottom * below.a * (1.0 - alpha)) / out_alpha;
+    image.set_pixel(x, y, Color::new(mix(color.r, below.r), mix(color.g, below.g), mix(color.b, below.b), out_alpha));
+}
+
 fn draw_circle_on_image(image: &mut macroquad::texture::Image, cx: u32, cy: u32, radius: u32, color: Color) {
     let (cx, cy, r) = (cx as i32, cy as i32, radius as i32);
     let mut x = r;
@@ -695,13 +4663,474 @@ fn draw_circle_on_image(image: &mut macroquad::texture::Image, cx: u32, cy: u32,
     }
 }
 
+/// Draw a digit from `DIGIT_GLYPHS` centered on (cx, cy), with each glyph pixel
+/// drawn as a square `pixel_size` pixels across
+fn draw_digit_on_image(image: &mut Image, cx: u32, cy: u32, digit: u8, pixel_size: u32, color: Color) {
+    let Some(glyph) = DIGIT_GLYPHS.get(digit as usize) else {
+        return;
+    };
+    let left = cx as i32 - (3 * pixel_size as i32) / 2;
+    let top = cy as i32 - (5 * pixel_size as i32) / 2;
+    let (w, h) = (image.width() as i32, image.height() as i32);
+    for (row, bits) in glyph.iter().enumerate() {
+        for column in 0..3 {
+            if bits & (0b100 >> column) == 0 {
+                continue;
+            }
+            for dy in 0..pixel_size as i32 {
+                for dx in 0..pixel_size as i32 {
+                    let px = left + column * pixel_size as i32 + dx;
+                    let py = top + row as i32 * pixel_size as i32 + dy;
+                    if px >= 0 && py >= 0 && px < w && py < h {
+                        image.set_pixel(px as u32, py as u32, color);
+                    }
+                }
+            }
+        }
+    }
+}
+
 /// Main entry point for the Macroquad application
-#[macroquad::main("Sigil-Gen")]
-async fn main() {
+fn main() {
+    let args: Vec<String> = std::env::args().collect();
+    // Scripts can generate and save a sigil without a window ever opening
+    if args.iter().any(|arg| arg == "--no-gui" || arg == "--from-file") {
+        std::process::exit(run_headless(&args));
+    }
+    let config = Config::load();
+    macroquad::Window::from_config(window_conf(&config), run(args, config));
+}
+
+/// Describe the window to open. Only the title, size, resizing and high-DPI mode take
+/// effect: macroquad 0.3 replaces the sample count with 4x multisampling whatever is asked
+/// for here, and on Windows miniquad only ever creates an OpenGL context, so there's no
+/// fallback to pick for machines that can't give it one. High-DPI mode can be turned off
+/// in the config for scaled displays that stay black with it on
+fn window_conf(config: &Config) -> Conf {
+    Conf {
+        window_title: "Sigil-Gen".to_string(),
+        window_width: WINDOW_SIZE,
+        window_height: WINDOW_SIZE,
+        window_resizable: true,
+        high_dpi: config.high_dpi.unwrap_or(true),
+        ..Default::default()
+    }
+}
+
+/// Generate one sigil from the intention or text file given on the command line and save it,
+/// returning the process's exit code
+fn run_headless(args: &[String]) -> i32 {
+    let mut app = SigilApp::new();
+    app.apply_config(&Config::load());
+    app.stats = UsageStats::load();
+    app.apply_args(args);
+    let variants = match arg_value(args, "--variants").map(|count| count.parse::<usize>()) {
+        None => 1,
+        Some(Ok(count)) if (1..=MAX_VARIANTS).contains(&count) => count,
+        Some(_) => {
+            eprintln!("--variants takes a count from 1 to {}", MAX_VARIANTS);
+            return 1;
+        }
+    };
+    let saved = match (arg_value(args, "--intention"), arg_value(args, "--from-file")) {
+        (Some(intention), _) => app.export_intention(&intention, variants),
+        (None, Some(path)) => app.export_from_file(Path::new(&path), variants),
+        (None, None) => Err("--no-gui needs an --intention or --from-file to generate from".to_string()),
+    };
+    match saved {
+        Ok(filenames) => {
+            for filename in filenames {
+                println!("Saved {}", filename);
+            }
+            0
+        }
+        Err(e) => {
+            eprintln!("Failed to generate sigil: {}", e);
+            1
+        }
+    }
+}
+
+/// Run the app in its window until it's closed
+async fn run(args: Vec<String>, config: Config) {
     let mut app = SigilApp::new();
+    app.apply_config(&config);
+    app.stats = UsageStats::load();
+    // Show the loading screen for a frame while the assets load
+    app.draw();
+    next_frame().await;
+    app.load_assets(&config).await;
+    app.apply_args(&args);
+    // Restore a saved sigil if a JSON file was given on the command line
+    if let Some(path) = arg_value(&args, "--load") {
+        match SigilRecord::load(Path::new(&path)) {
+            Ok(record) => app.apply_record(record),
+            Err(e) => eprintln!("Failed to load sigil: {}", e),
+        }
+    }
+    // Reconstruct a shared sigil if a code was given on the command line
+    if let Some(code) = arg_value(&args, "--code") {
+        match SigilCode::decode(&code) {
+            Ok(code) => app.apply_code(code),
+            Err(e) => eprintln!("Failed to load sigil code: {}", e),
+        }
+    }
+    // Closing the window ends the loop below so the app can tidy up first
+    prevent_quit();
     loop {
+        if is_quit_requested() || app.quitting {
+            app.clean_up();
+            break;
+        }
+        // Nothing can be seen while minimized, so hold the animation and drawing
+        // still and only check back occasionally instead of spinning every frame
+        if window_minimized() {
+            std::thread::sleep(MINIMIZED_FRAME_DELAY);
+            next_frame().await;
+            continue;
+        }
         app.update();
         app.draw();
+        // Screenshots grab the finished frame, so they're taken after drawing
+        if is_key_pressed(KeyCode::F12) && !app.focus_locked {
+            match app.save_screenshot() {
+                Ok(path) => app.show_status(&format!("Saved screenshot {}", full_path(&path.display().to_string()))),
+                Err(e) => {
+                    eprintln!("Failed to save screenshot: {}", e);
+                    app.show_status(&format!("Couldn't save screenshot: {}", e));
+                }
+            }
+        }
         next_frame().await;
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn boundary_at_x_picks_the_closest_character_edge() {
+        // "abc" with every character 10 pixels wide, and a two-byte character in the middle
+        let widths = [(0, 10.0), (1, 10.0), (3, 10.0)];
+        assert_eq!(boundary_at_x(widths, 4, -5.0), 0);
+        assert_eq!(boundary_at_x(widths, 4, 4.0), 0);
+        assert_eq!(boundary_at_x(widths, 4, 6.0), 1);
+        assert_eq!(boundary_at_x(widths, 4, 16.0), 3);
+        assert_eq!(boundary_at_x(widths, 4, 26.0), 4);
+        assert_eq!(boundary_at_x(widths, 4, 500.0), 4);
+    }
+
+    #[test]
+    fn boundary_at_x_on_empty_text_is_the_end() {
+        assert_eq!(boundary_at_x([], 7, 12.0), 7);
+    }
+
+    /// Generate the sigil for an intention from a fixed seed, returning where its points land
+    fn generated_positions(intention: &str, seed: u64) -> Vec<Vec2> {
+        let mut app = SigilApp::new();
+        app.intention = intention.to_string();
+        app.seed = seed;
+        app.layout_seed = seed;
+        app.generate_sigil_with_seed();
+        app.points.iter().map(|point| point.relative_pos).collect()
+    }
+
+    #[test]
+    fn same_seed_gives_the_same_points() {
+        let first = generated_positions("protect my home", 42);
+        assert!(!first.is_empty());
+        assert_eq!(first, generated_positions("protect my home", 42));
+        assert_ne!(first, generated_positions("protect my home", 43));
+    }
+
+    #[test]
+    fn advance_animation_landing_exactly_on_a_segment_end_starts_the_next() {
+        assert_eq!(advance_animation(0.75, 2, 0.25, 5), Some((0.0, 3)));
+    }
+
+    #[test]
+    fn advance_animation_carries_overshoot_across_several_segments() {
+        let (progress, line) = advance_animation(0.5, 0, 2.75, 10).unwrap();
+        assert_eq!(line, 3);
+        assert!((progress - 0.25).abs() < 1e-6);
+    }
+
+    #[test]
+    fn advance_animation_past_the_last_segment_is_done() {
+        assert_eq!(advance_animation(0.5, 4, 0.25, 5), Some((0.75, 4)));
+        assert_eq!(advance_animation(0.9, 4, 0.1, 5), None);
+        assert_eq!(advance_animation(0.0, 0, 9.0, 5), None);
+    }
+
+    #[test]
+    fn rewind_animation_steps_back_and_stops_at_the_start() {
+        assert_eq!(rewind_animation(0.25, 3, 0.25), Some((0.0, 3)));
+        let (progress, line) = rewind_animation(0.25, 3, 1.5).unwrap();
+        assert_eq!(line, 1);
+        assert!((progress - 0.75).abs() < 1e-6);
+        assert_eq!(rewind_animation(0.25, 0, 0.5), None);
+    }
+
+    /// An app holding `text` with `anchor` and `cursor` as the selection's two ends
+    fn app_with_selection(text: &str, anchor: usize, cursor: usize) -> SigilApp {
+        let mut app = SigilApp::new();
+        app.intention = text.to_string();
+        app.selection_start = Some(anchor);
+        app.cursor_pos = cursor;
+        app
+    }
+
+    #[test]
+    fn selection_range_is_ordered_whichever_way_it_was_made() {
+        assert_eq!(app_with_selection("hello world", 0, 5).selection_range(), Some((0, 5)));
+        assert_eq!(app_with_selection("hello world", 11, 6).selection_range(), Some((6, 11)));
+        assert_eq!(app_with_selection("hello world", 4, 4).selection_range(), None);
+    }
+
+    #[test]
+    fn typing_replaces_a_forward_or_backward_selection() {
+        let mut forward = app_with_selection("hello world", 0, 5);
+        forward.insert_text("j");
+        assert_eq!((forward.intention.as_str(), forward.cursor_pos), ("j world", 1));
+        let mut backward = app_with_selection("hello world", 11, 6);
+        backward.insert_text("x");
+        assert_eq!((backward.intention.as_str(), backward.cursor_pos), ("hello x", 7));
+        assert_eq!(backward.selection_start, None);
+    }
+
+    #[test]
+    fn pasting_replaces_the_selection_within_the_length_limit() {
+        let mut app = app_with_selection("hello world", 11, 6);
+        app.insert_text("there");
+        assert_eq!((app.intention.as_str(), app.cursor_pos), ("hello there", 11));
+        // The selected text makes room for the paste, and the rest is dropped
+        let mut app = app_with_selection("abcdef", 2, 4);
+        app.max_intention_len = 7;
+        app.insert_text("12345");
+        assert_eq!((app.intention.as_str(), app.cursor_pos), ("ab123ef", 5));
+    }
+
+    #[test]
+    fn deleting_removes_a_forward_or_backward_selection() {
+        let mut forward = app_with_selection("hello world", 0, 6);
+        assert!(forward.delete_selection());
+        assert_eq!((forward.intention.as_str(), forward.cursor_pos), ("world", 0));
+        let mut backward = app_with_selection("hello world", 11, 5);
+        assert!(backward.delete_selection());
+        assert_eq!((backward.intention.as_str(), backward.cursor_pos), ("hello", 5));
+        let mut empty = app_with_selection("hello", 2, 2);
+        assert!(!empty.delete_selection());
+        assert_eq!(empty.intention, "hello");
+    }
+
+    /// Just the numbers an intention maps to
+    fn numbers(intention: &str, keep_vowels: bool, scheme: NumberScheme) -> Vec<u8> {
+        intention_to_numbers(intention, keep_vowels, scheme).into_iter().map(|(_, n)| n).collect()
+    }
+
+    #[test]
+    fn intention_to_numbers_removes_vowels_unless_kept() {
+        assert_eq!(filter_letters("Protect me", false), "prtcm");
+        assert_eq!(filter_letters("Protect me", true), "protecm");
+        assert_eq!(numbers("bead", false, NumberScheme::SimpleMod10), vec![1, 3]);
+        assert_eq!(numbers("bead", true, NumberScheme::SimpleMod10), vec![1, 4, 0, 3]);
+    }
+
+    #[test]
+    fn intention_to_numbers_drops_repeated_letters() {
+        assert_eq!(filter_letters("Bb bob", true), "bo");
+        assert_eq!(numbers("tttt", false, NumberScheme::SimpleMod10), vec![9]);
+    }
+
+    #[test]
+    fn intention_to_numbers_keeps_digits_as_themselves() {
+        for scheme in [NumberScheme::SimpleMod10, NumberScheme::Pythagorean, NumberScheme::Chaldean] {
+            assert_eq!(numbers("2024 x", false, scheme)[..3], [2, 0, 4]);
+        }
+        assert_eq!(intention_to_numbers("b7", false, NumberScheme::Pythagorean), vec![('b', 2), ('7', 7)]);
+    }
+
+    #[test]
+    fn intention_to_numbers_of_blank_input_is_empty() {
+        assert!(intention_to_numbers("", false, NumberScheme::SimpleMod10).is_empty());
+        assert!(intention_to_numbers("   \n\t", true, NumberScheme::SimpleMod10).is_empty());
+        assert!(intention_to_numbers("aeiou !?", false, NumberScheme::Chaldean).is_empty());
+    }
+
+    #[test]
+    fn short_segments_merge_into_their_start_point() {
+        // Nearly straight run along the top where the middle steps are tiny jitters
+        let positions = [vec2(-1.0, 0.0), vec2(-0.99, 0.001), vec2(-0.98, -0.001), vec2(0.0, 0.0), vec2(0.005, 0.0), vec2(1.0, 0.0)];
+        let mut points: Vec<SigilPoint> = positions
+            .iter()
+            .enumerate()
+            .map(|(i, &relative_pos)| SigilPoint { relative_pos, number: i as u8, source: (b'a' + i as u8) as char })
+            .collect();
+        merge_short_segments(&mut points, 0.05);
+        let kept: Vec<Vec2> = points.iter().map(|p| p.relative_pos).collect();
+        assert_eq!(kept, vec![vec2(-1.0, 0.0), vec2(0.0, 0.0), vec2(1.0, 0.0)]);
+        assert_eq!(points.iter().map(|p| p.number).collect::<Vec<_>>(), vec![0, 3, 5]);
+        assert_eq!(points.iter().map(|p| p.source).collect::<String>(), "adf");
+    }
+
+    #[test]
+    fn zero_min_segment_length_keeps_every_point() {
+        let mut points = vec![SigilPoint { relative_pos: Vec2::ZERO, number: 1, source: 'b' }; 3];
+        merge_short_segments(&mut points, 0.0);
+        assert_eq!(points.len(), 3);
+    }
+
+    #[test]
+    fn re_angling_keeps_the_full_sequence() {
+        let mut app = SigilApp::new();
+        app.intention = "the quick brown fox jumps over the lazy dog".to_string();
+        app.min_segment_length = 150.0;
+        app.seed = 1;
+        app.layout_seed = 1;
+        app.generate_sigil_with_seed();
+        let (numbers, sources) = (app.numbers.clone(), app.sources.clone());
+        assert!(app.points.len() < numbers.len());
+        for _ in 0..10 {
+            app.regenerate_angles();
+            assert_eq!((&app.numbers, &app.sources), (&numbers, &sources));
+            assert!(!app.points.is_empty() && app.points.len() <= numbers.len());
+        }
+    }
+
+    #[test]
+    fn share_code_reproduces_a_merged_sigil() {
+        let mut app = SigilApp::new();
+        app.intention = "the quick brown fox jumps over the lazy dog".to_string();
+        app.min_segment_length = 150.0;
+        app.generate_sigil_with_seed();
+        app.regenerate_angles();
+        let mut shared = SigilApp::new();
+        shared.apply_code(app.sigil_code());
+        let positions = |app: &SigilApp| app.points.iter().map(|p| p.relative_pos).collect::<Vec<_>>();
+        assert_eq!(positions(&shared), positions(&app));
+    }
+
+    #[test]
+    fn saved_json_loads_back_the_same_sigil() {
+        let mut app = SigilApp::new();
+        app.intention = "protect my home".to_string();
+        app.note = "full moon".to_string();
+        app.save_json = true;
+        app.generate_sigil_with_seed();
+        let image_path = std::env::temp_dir().join(format!("sigil_round_trip_{}.png", std::process::id()));
+        app.save_metadata(&image_path).unwrap();
+        let json_path = image_path.with_extension("json");
+        let record = SigilRecord::load(&json_path);
+        std::fs::remove_file(&json_path).unwrap();
+
+        let mut loaded = SigilApp::new();
+        loaded.apply_record(record.unwrap());
+        assert!(matches!(loaded.state, State::Display));
+        assert_eq!((loaded.intention.as_str(), loaded.note.as_str()), ("protect my home", "full moon"));
+        assert_eq!((loaded.seed, loaded.layout_seed), (app.seed, app.layout_seed));
+        assert_eq!(loaded.numbers, app.numbers);
+        assert_eq!(loaded.sources, app.sources);
+        let positions = |app: &SigilApp| app.points.iter().map(|p| p.relative_pos).collect::<Vec<_>>();
+        assert_eq!(positions(&loaded), positions(&app));
+    }
+
+    #[test]
+    fn collapse_merges_runs_of_equal_numbers() {
+        let mut numbers = vec![3, 3, 3, 7, 3, 1, 1];
+        let mut sources = vec!['c', 'm', 'w', 'g', 'x', 'a', 'k'];
+        collapse_repeated_numbers(&mut numbers, &mut sources);
+        assert_eq!(numbers, vec![3, 7, 3, 1]);
+        assert_eq!(sources, vec!['c', 'g', 'x', 'a']);
+    }
+
+    #[test]
+    fn collapse_repeats_shortens_the_generated_sequence() {
+        // b, l and v all map to 1 in the simple scheme; f maps to 5
+        let mut app = SigilApp::new();
+        app.intention = "blvf".to_string();
+        app.number_scheme = NumberScheme::SimpleMod10;
+        app.preserve_order = true;
+        app.salt = 0;
+        app.min_segment_length = 0.0;
+        app.generate_sigil_with_seed();
+        assert_eq!(app.numbers, vec![1, 1, 1, 5]);
+        app.collapse_repeats = true;
+        app.generate_sigil_with_seed();
+        assert_eq!(app.numbers, vec![1, 5]);
+        assert_eq!(app.sources, vec!['b', 'f']);
+        assert_eq!(app.points.len(), 2);
+    }
+
+    #[test]
+    fn srgb_encoding_of_known_colors() {
+        assert_eq!(linear_to_srgb(0.0), 0.0);
+        assert!((linear_to_srgb(1.0) - 1.0).abs() < 1e-6);
+        // Linear mid grey is much lighter once encoded
+        assert!((linear_to_srgb(0.5) - 0.7354).abs() < 1e-3);
+        let mut image = Image { bytes: vec![0, 128, 255, 128, 255, 0, 64, 0], width: 2, height: 1 };
+        encode_srgb(&mut image);
+        assert_eq!(image.bytes, vec![0, 188, 255, 128, 255, 0, 137, 0]);
+    }
+
+    #[test]
+    fn explicit_output_is_only_overwritten_when_forced() {
+        let path = std::env::temp_dir().join(format!("sigil_overwrite_{}.png", std::process::id()));
+        std::fs::write(&path, "old").unwrap();
+        let mut app = SigilApp::new();
+        app.output_path = Some(path.clone());
+        let refused = app.output_filename().unwrap_err();
+        assert_eq!(refused.kind(), std::io::ErrorKind::AlreadyExists);
+        app.force_overwrite = true;
+        let forced = app.output_filename();
+        std::fs::remove_file(&path).unwrap();
+        assert_eq!(forced.unwrap(), path.to_string_lossy());
+    }
+
+    #[test]
+    fn size_export_ignores_an_existing_base_file() {
+        let path = std::env::temp_dir().join(format!("sigil_size_base_{}.png", std::process::id()));
+        std::fs::write(&path, "old").unwrap();
+        let mut app = SigilApp::new();
+        app.output_path = Some(path.clone());
+        let started = app.start_size_export();
+        let taken = size_variant_path(&path, EXPORT_VARIANT_SIZES[0]);
+        std::fs::write(&taken, "old").unwrap();
+        let blocked = app.start_size_export();
+        std::fs::remove_file(&path).unwrap();
+        std::fs::remove_file(&taken).unwrap();
+        assert!(started.is_ok());
+        assert_eq!(blocked.unwrap_err().kind(), std::io::ErrorKind::AlreadyExists);
+    }
+
+    #[test]
+    fn variants_are_saved_with_numbered_names() {
+        let dir = std::env::temp_dir().join(format!("sigil_variants_{}", std::process::id()));
+        let mut app = SigilApp::new();
+        app.output_path = Some(dir.join("batch.png"));
+        app.frozen_seed = Some(7);
+        let saved = app.export_intention("protect my home", 3);
+        std::fs::remove_dir_all(&dir).unwrap();
+        let names: Vec<String> = saved
+            .unwrap()
+            .iter()
+            .map(|path| Path::new(path).file_name().unwrap().to_string_lossy().into_owned())
+            .collect();
+        assert_eq!(names, ["batch_v1.png", "batch_v2.png", "batch_v3.png"]);
+    }
+
+    #[test]
+    fn refused_batch_writes_nothing() {
+        let dir = std::env::temp_dir().join(format!("sigil_refused_batch_{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        std::fs::write(dir.join("batch_v3.png"), "old").unwrap();
+        let mut app = SigilApp::new();
+        app.output_path = Some(dir.join("batch.png"));
+        let result = app.export_intention("protect my home", 3);
+        let written = std::fs::read_dir(&dir).unwrap().count();
+        std::fs::remove_dir_all(&dir).unwrap();
+        assert!(result.unwrap_err().contains("batch_v3.png"));
+        assert_eq!(written, 1);
+        assert_eq!((app.stats.generated, app.stats.saved), (0, 0));
+    }
+}
@@ -0,0 +1,106 @@
+use serde::{Deserialize, Serialize};
+use std::path::{Path, PathBuf};
+
+// Version of the JSON sigil format, bumped whenever the layout changes
+pub const RECORD_VERSION: u32 = 1;
+
+/// A saved sigil: everything needed to redraw it exactly as it was
+#[derive(Debug, Serialize, Deserialize)]
+pub struct SigilRecord {
+    pub version: u32,             // Format version
+    pub intention: String,        // The intention text
+    #[serde(default)]
+    pub note: String,             // Free-text note attached when saving
+    #[serde(default)]
+    pub letters: String,          // Characters of the intention left after filtering, in order
+    #[serde(default)]
+    pub salt: u32,                // Personal number added to each number in the sequence
+    pub seed: u64,                // Seed used to shuffle the number sequence
+    pub layout_seed: u64,         // Seed used to place the points
+    pub points: Vec<RecordPoint>, // The points in path order
+}
+
+/// A single saved point, positioned relative to the circle's center
+#[derive(Debug, Serialize, Deserialize)]
+pub struct RecordPoint {
+    pub number: u8,
+    #[serde(default)]
+    pub source: Option<char>,
+    pub x: f32,
+    pub y: f32,
+}
+
+impl SigilRecord {
+    /// Serialize the record as pretty-printed JSON
+    pub fn to_json(&self) -> String {
+        serde_json::to_string_pretty(self).unwrap_or_default()
+    }
+
+    /// Parse and validate a record from JSON text
+    pub fn from_json(text: &str) -> Result<Self, String> {
+        let record: Self = serde_json::from_str(text).map_err(|e| format!("malformed sigil file: {}", e))?;
+        if record.version != RECORD_VERSION {
+            return Err(format!("unsupported sigil file version {}", record.version));
+        }
+        if record.points.is_empty() {
+            return Err("malformed sigil file: no points".to_string());
+        }
+        if record.points.iter().any(|p| p.number > 9 || !p.x.is_finite() || !p.y.is_finite()) {
+            return Err("malformed sigil file: invalid point".to_string());
+        }
+        Ok(record)
+    }
+
+    /// Read and validate a record from a JSON file
+    pub fn load(path: &Path) -> Result<Self, String> {
+        let text = std::fs::read_to_string(path).map_err(|e| format!("{}: {}", path.display(), e))?;
+        Self::from_json(&text).map_err(|e| format!("{}: {}", path.display(), e))
+    }
+}
+
+/// Find the most recently modified sigil JSON file in a directory
+pub fn latest_record(dir: &Path) -> Option<PathBuf> {
+    std::fs::read_dir(dir)
+        .ok()?
+        .filter_map(|entry| entry.ok())
+        .map(|entry| entry.path())
+        .filter(|path| path.extension().is_some_and(|ext| ext == "json"))
+        .max_by_key(|path| path.metadata().and_then(|m| m.modified()).ok())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn record_json(version: u32) -> String {
+        format!(
+            r#"{{"version": {}, "intention": "hi", "seed": 1, "layout_seed": 2, "points": [{{"number": 3, "x": 0.5, "y": -0.5}}]}}"#,
+            version
+        )
+    }
+
+    #[test]
+    fn current_version_parses() {
+        let record = SigilRecord::from_json(&record_json(RECORD_VERSION)).unwrap();
+        assert_eq!(record.intention, "hi");
+        assert_eq!(record.points[0].source, None);
+    }
+
+    #[test]
+    fn malformed_json_is_rejected() {
+        for text in ["", "{", "not json", r#"{"version": 1, "intention": "hi"}"#] {
+            let err = SigilRecord::from_json(text).unwrap_err();
+            assert!(err.starts_with("malformed sigil file"), "{}", err);
+        }
+        let no_points = record_json(RECORD_VERSION).replace(r#"{"number": 3, "x": 0.5, "y": -0.5}"#, "");
+        assert_eq!(SigilRecord::from_json(&no_points).unwrap_err(), "malformed sigil file: no points");
+        let bad_number = record_json(RECORD_VERSION).replace(r#""number": 3"#, r#""number": 12"#);
+        assert_eq!(SigilRecord::from_json(&bad_number).unwrap_err(), "malformed sigil file: invalid point");
+    }
+
+    #[test]
+    fn unsupported_version_is_rejected() {
+        let err = SigilRecord::from_json(&record_json(RECORD_VERSION + 1)).unwrap_err();
+        assert_eq!(err, format!("unsupported sigil file version {}", RECORD_VERSION + 1));
+    }
+}
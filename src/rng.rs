@@ -0,0 +1,44 @@
+// The standard PCG-XSH-RR increment and multiplier
+const INCREMENT: u64 = 1442695040888963407;
+const MULTIPLIER: u64 = 6364136223846793005;
+
+/// A small seeded pseudo-random generator (PCG-XSH-RR). Everything random about a sigil
+/// is drawn from one of these, so the same intention and seed always give the same sigil,
+/// whatever else in the app uses random numbers and whichever macroquad version is built
+pub struct SeededRng {
+    state: u64,
+}
+
+impl SeededRng {
+    /// Create a generator whose sequence is fixed by `seed`
+    pub fn new(seed: u64) -> Self {
+        let mut rng = Self { state: 0 };
+        rng.next_u32();
+        rng.state = rng.state.wrapping_add(seed);
+        rng.next_u32();
+        rng
+    }
+
+    /// Get the next number in the sequence
+    pub fn next_u32(&mut self) -> u32 {
+        let old = self.state;
+        self.state = old.wrapping_mul(MULTIPLIER).wrapping_add(INCREMENT);
+        let xorshifted = (((old >> 18) ^ old) >> 27) as u32;
+        xorshifted.rotate_right((old >> 59) as u32)
+    }
+
+    /// Get a number in 0..1
+    fn next_f64(&mut self) -> f64 {
+        self.next_u32() as f64 / (u32::MAX as f64 + 1.0)
+    }
+
+    /// Get an index in `low..high`
+    pub fn index(&mut self, low: usize, high: usize) -> usize {
+        (low as f64 + (high - low) as f64 * self.next_f64()) as usize
+    }
+
+    /// Get a value in `low..high`
+    pub fn range(&mut self, low: f32, high: f32) -> f32 {
+        (low as f64 + (high as f64 - low as f64) * self.next_f64()) as f32
+    }
+}
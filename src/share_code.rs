@@ -0,0 +1,234 @@
+use crate::{AngleMode, Frame, NumberScheme, MAX_POLYGON_SIDES, MIN_POLYGON_SIDES};
+use base64::{engine::general_purpose::URL_SAFE_NO_PAD, Engine as _};
+
+// Version of the sigil code format, bumped whenever the byte layout changes
+const CODE_VERSION: u8 = 4;
+// Oldest code version that can still be decoded (it has no second flags byte)
+const MIN_CODE_VERSION: u8 = 2;
+// First code version with the frame and minimum segment length
+const FRAME_CODE_VERSION: u8 = 4;
+
+// Number of bytes before the intention text
+const HEADER_LEN: usize = 24;
+// Bytes the frame and minimum segment length take up at the end of the header
+const FRAME_LEN: usize = 5;
+
+// Flag bits stored in the settings byte
+const FLAG_EMPHASIZE_POWER: u8 = 1 << 0;
+const FLAG_GHOST_VOWELS: u8 = 1 << 1;
+// Two bits of the settings byte hold the angle mode (0 in older codes, meaning jittered)
+const ANGLE_MODE_SHIFT: u8 = 2;
+const ANGLE_MODE_MASK: u8 = 0b11;
+// The top four bits hold the salt's effect on the numbers (0-9)
+const SALT_SHIFT: u8 = 4;
+
+// Flag bits stored in the second settings byte
+const FLAG_COLLAPSE_REPEATS: u8 = 1 << 0;
+const FLAG_KEEP_VOWELS: u8 = 1 << 1;
+const FLAG_RADIAL_MODE: u8 = 1 << 2;
+const FLAG_PRESERVE_ORDER: u8 = 1 << 5;
+// Two bits of the second settings byte hold the number scheme (0 in older codes, meaning simple)
+const NUMBER_SCHEME_SHIFT: u8 = 3;
+const NUMBER_SCHEME_MASK: u8 = 0b11;
+
+/// The generation parameters needed to reproduce a sigil exactly
+#[derive(Clone, Debug, PartialEq)]
+pub struct SigilCode {
+    pub intention: String,     // The intention text
+    pub seed: u64,             // Seed used to shuffle the number sequence
+    pub layout_seed: u64,      // Seed used to place the points around the circle
+    pub emphasize_power: bool, // Whether the power number is emphasized
+    pub ghost_vowels: bool,    // Whether vowels are shown as ghost points
+    pub angle_mode: AngleMode, // How the points' angles were chosen
+    pub salt: u8,              // Salt added to each number, already reduced mod 10
+    pub collapse_repeats: bool, // Whether runs of the same number were merged into one point
+    pub keep_vowels: bool,     // Whether vowels were kept in the intention
+    pub radial_mode: bool,     // Whether each point's distance from the center followed its number
+    pub preserve_order: bool,  // Whether the letters were traced in the order they're written
+    pub number_scheme: NumberScheme, // How the letters were turned into numbers
+    pub frame: Frame,          // The shape enclosing the sigil
+    pub min_segment_length: f32, // Segments shorter than this (in pixels) were merged away
+}
+
+impl SigilCode {
+    /// Encode the parameters as a compact URL-safe base64 string.
+    /// Layout: version (1 byte), seed (8 bytes LE), layout seed (8 bytes LE),
+    /// flags (1 byte), more flags (1 byte), frame sides (1 byte, 0 for a circle),
+    /// minimum segment length (4 bytes LE), intention (rest)
+    pub fn encode(&self) -> String {
+        let mut bytes = Vec::with_capacity(HEADER_LEN + self.intention.len());
+        bytes.push(CODE_VERSION);
+        bytes.extend_from_slice(&self.seed.to_le_bytes());
+        bytes.extend_from_slice(&self.layout_seed.to_le_bytes());
+        let mut flags = 0;
+        if self.emphasize_power {
+            flags |= FLAG_EMPHASIZE_POWER;
+        }
+        if self.ghost_vowels {
+            flags |= FLAG_GHOST_VOWELS;
+        }
+        let angle_mode = match self.angle_mode {
+            AngleMode::Jittered => 0,
+            AngleMode::Even => 1,
+            AngleMode::FromNumber => 2,
+        };
+        flags |= angle_mode << ANGLE_MODE_SHIFT;
+        flags |= (self.salt % 10) << SALT_SHIFT;
+        bytes.push(flags);
+        let mut more_flags = 0;
+        if self.collapse_repeats {
+            more_flags |= FLAG_COLLAPSE_REPEATS;
+        }
+        if self.keep_vowels {
+            more_flags |= FLAG_KEEP_VOWELS;
+        }
+        if self.radial_mode {
+            more_flags |= FLAG_RADIAL_MODE;
+        }
+        if self.preserve_order {
+            more_flags |= FLAG_PRESERVE_ORDER;
+        }
+        let number_scheme = match self.number_scheme {
+            NumberScheme::SimpleMod10 => 0,
+            NumberScheme::Pythagorean => 1,
+            NumberScheme::Chaldean => 2,
+        };
+        more_flags |= number_scheme << NUMBER_SCHEME_SHIFT;
+        bytes.push(more_flags);
+        bytes.push(match self.frame {
+            Frame::Circle => 0,
+            Frame::Polygon(sides) => sides,
+        });
+        bytes.extend_from_slice(&self.min_segment_length.to_le_bytes());
+        bytes.extend_from_slice(self.intention.as_bytes());
+        URL_SAFE_NO_PAD.encode(bytes)
+    }
+
+    /// Decode a string produced by `encode`, validating the format version.
+    /// Older codes, from before the second flags byte or the frame, are still accepted
+    /// and decode with a circle frame and no segment merging
+    pub fn decode(code: &str) -> Result<Self, String> {
+        let bytes = URL_SAFE_NO_PAD
+            .decode(code.trim())
+            .map_err(|e| format!("invalid sigil code: {}", e))?;
+        if bytes.is_empty() {
+            return Err("invalid sigil code: too short".to_string());
+        }
+        let version = bytes[0];
+        if !(MIN_CODE_VERSION..=CODE_VERSION).contains(&version) {
+            return Err(format!("unsupported sigil code version {}", version));
+        }
+        let header_len = match version {
+            MIN_CODE_VERSION => HEADER_LEN - FRAME_LEN - 1,
+            v if v < FRAME_CODE_VERSION => HEADER_LEN - FRAME_LEN,
+            _ => HEADER_LEN,
+        };
+        if bytes.len() < header_len {
+            return Err("invalid sigil code: too short".to_string());
+        }
+        let read_u64 = |offset: usize| {
+            let mut buf = [0u8; 8];
+            buf.copy_from_slice(&bytes[offset..offset + 8]);
+            u64::from_le_bytes(buf)
+        };
+        let flags = bytes[17];
+        let more_flags = if version == MIN_CODE_VERSION { 0 } else { bytes[18] };
+        let angle_mode = match (flags >> ANGLE_MODE_SHIFT) & ANGLE_MODE_MASK {
+            0 => AngleMode::Jittered,
+            1 => AngleMode::Even,
+            2 => AngleMode::FromNumber,
+            other => return Err(format!("invalid sigil code: unknown angle mode {}", other)),
+        };
+        let number_scheme = match (more_flags >> NUMBER_SCHEME_SHIFT) & NUMBER_SCHEME_MASK {
+            0 => NumberScheme::SimpleMod10,
+            1 => NumberScheme::Pythagorean,
+            2 => NumberScheme::Chaldean,
+            other => return Err(format!("invalid sigil code: unknown number scheme {}", other)),
+        };
+        let (frame, min_segment_length) = if version < FRAME_CODE_VERSION {
+            (Frame::Circle, 0.0)
+        } else {
+            let frame = match bytes[19] {
+                0 => Frame::Circle,
+                sides @ MIN_POLYGON_SIDES..=MAX_POLYGON_SIDES => Frame::Polygon(sides),
+                other => return Err(format!("invalid sigil code: unsupported frame with {} sides", other)),
+            };
+            let mut buf = [0u8; 4];
+            buf.copy_from_slice(&bytes[20..24]);
+            let min_segment_length = f32::from_le_bytes(buf);
+            if !min_segment_length.is_finite() || min_segment_length < 0.0 {
+                return Err("invalid sigil code: bad minimum segment length".to_string());
+            }
+            (frame, min_segment_length)
+        };
+        let salt = flags >> SALT_SHIFT;
+        if salt > 9 {
+            return Err(format!("invalid sigil code: salt {} out of range", salt));
+        }
+        let intention = String::from_utf8(bytes[header_len..].to_vec())
+            .map_err(|_| "invalid sigil code: intention is not valid text".to_string())?;
+        Ok(Self {
+            intention,
+            seed: read_u64(1),
+            layout_seed: read_u64(9),
+            emphasize_power: flags & FLAG_EMPHASIZE_POWER != 0,
+            ghost_vowels: flags & FLAG_GHOST_VOWELS != 0,
+            angle_mode,
+            salt,
+            collapse_repeats: more_flags & FLAG_COLLAPSE_REPEATS != 0,
+            keep_vowels: more_flags & FLAG_KEEP_VOWELS != 0,
+            radial_mode: more_flags & FLAG_RADIAL_MODE != 0,
+            preserve_order: more_flags & FLAG_PRESERVE_ORDER != 0,
+            number_scheme,
+            frame,
+            min_segment_length,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn code(frame: Frame, min_segment_length: f32) -> SigilCode {
+        SigilCode {
+            intention: "protect my home".to_string(),
+            seed: 42,
+            layout_seed: 7,
+            emphasize_power: true,
+            ghost_vowels: false,
+            angle_mode: AngleMode::Even,
+            salt: 3,
+            collapse_repeats: true,
+            keep_vowels: false,
+            radial_mode: false,
+            preserve_order: true,
+            number_scheme: NumberScheme::Chaldean,
+            frame,
+            min_segment_length,
+        }
+    }
+
+    #[test]
+    fn frame_and_segment_length_round_trip() {
+        for original in [code(Frame::Circle, 0.0), code(Frame::Polygon(5), 12.5)] {
+            assert_eq!(SigilCode::decode(&original.encode()), Ok(original));
+        }
+    }
+
+    #[test]
+    fn version_3_codes_decode_with_a_plain_circle() {
+        let mut bytes = URL_SAFE_NO_PAD.decode(code(Frame::Polygon(6), 4.0).encode()).unwrap();
+        bytes[0] = 3;
+        bytes.drain(HEADER_LEN - FRAME_LEN..HEADER_LEN);
+        let decoded = SigilCode::decode(&URL_SAFE_NO_PAD.encode(bytes)).unwrap();
+        assert_eq!(decoded, code(Frame::Circle, 0.0));
+    }
+
+    #[test]
+    fn unsupported_frame_is_rejected() {
+        let mut bytes = URL_SAFE_NO_PAD.decode(code(Frame::Circle, 0.0).encode()).unwrap();
+        bytes[19] = MAX_POLYGON_SIDES + 1;
+        assert!(SigilCode::decode(&URL_SAFE_NO_PAD.encode(bytes)).is_err());
+    }
+}
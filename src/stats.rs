@@ -0,0 +1,85 @@
+use directories::ProjectDirs;
+use serde::{Deserialize, Serialize};
+use std::path::{Path, PathBuf};
+
+// Name of the stats file inside the platform config directory
+const STATS_FILE: &str = "stats.json";
+
+/// Counts of sigils made on this machine. Only ever stored locally, never sent anywhere.
+#[derive(Debug, Default, Serialize, Deserialize)]
+pub struct UsageStats {
+    pub generated: u64, // Sigils generated from an intention
+    pub saved: u64,     // Sigils saved to a file
+    #[serde(skip)]
+    path: Option<PathBuf>, // File the stats were loaded from and are saved to; None keeps them in memory
+}
+
+impl UsageStats {
+    /// Get where the stats are kept, next to the config file
+    fn default_path() -> Option<PathBuf> {
+        ProjectDirs::from("", "", "sigil-gen").map(|dirs| dirs.config_dir().join(STATS_FILE))
+    }
+
+    /// Load the saved stats, starting from zero if there are none or they can't be read
+    pub fn load() -> Self {
+        match Self::default_path() {
+            Some(path) => Self::load_from(&path),
+            None => Self::default(),
+        }
+    }
+
+    /// Load the stats kept in a particular file, which later saves write back to
+    pub fn load_from(path: &Path) -> Self {
+        let mut stats = if path.is_file() {
+            let parsed = std::fs::read_to_string(path)
+                .map_err(|e| e.to_string())
+                .and_then(|text| serde_json::from_str(&text).map_err(|e| e.to_string()));
+            parsed.unwrap_or_else(|e| {
+                eprintln!("Failed to load usage stats from {}: {}", path.display(), e);
+                Self::default()
+            })
+        } else {
+            Self::default()
+        };
+        stats.path = Some(path.to_path_buf());
+        stats
+    }
+
+    /// Set both counts back to zero, keeping where they're saved
+    pub fn reset(&mut self) {
+        self.generated = 0;
+        self.saved = 0;
+    }
+
+    /// Write the stats back to the file they came from, if any
+    pub fn save(&self) {
+        let Some(path) = &self.path else {
+            return;
+        };
+        let written = path
+            .parent()
+            .map_or(Ok(()), std::fs::create_dir_all)
+            .and_then(|_| std::fs::write(path, serde_json::to_string_pretty(self).unwrap_or_default()));
+        if let Err(e) = written {
+            eprintln!("Failed to save usage stats to {}: {}", path.display(), e);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn stats_save_back_to_the_file_they_were_loaded_from() {
+        let path = std::env::temp_dir().join(format!("sigil_stats_{}.json", std::process::id()));
+        let mut stats = UsageStats::load_from(&path);
+        assert_eq!((stats.generated, stats.saved), (0, 0));
+        stats.generated = 4;
+        stats.saved = 2;
+        stats.save();
+        let reloaded = UsageStats::load_from(&path);
+        std::fs::remove_file(&path).unwrap();
+        assert_eq!((reloaded.generated, reloaded.saved), (4, 2));
+    }
+}
@@ -0,0 +1,129 @@
+use crate::{ExportStyle, Frame, SigilApp, CIRCLE_RADIUS, GHOST_COLOR, GHOST_RADIUS};
+use macroquad::prelude::*;
+use std::fmt::Write as _;
+
+// Width and height of the exported SVG
+const SVG_SIZE: f32 = 600.0;
+
+/// Format a color as an SVG hex color, ignoring alpha
+fn hex(color: Color) -> String {
+    let [r, g, b, _]: [u8; 4] = color.into();
+    format!("#{:02x}{:02x}{:02x}", r, g, b)
+}
+
+impl SigilApp {
+    /// Get the color an SVG draws `color` in: as on screen, or in the ink of a monochrome export style
+    fn svg_color(&self, color: Color) -> Color {
+        match self.export_style {
+            ExportStyle::Color => self.ink(color),
+            _ => self.style_ink(color),
+        }
+    }
+
+    /// Build an SVG of the sigil whose lines draw themselves in a browser,
+    /// one after another in the same order and at the same speed as in the app
+    pub(crate) fn animated_svg(&self) -> String {
+        let c = SVG_SIZE / 2.0;
+        let mut svg = String::new();
+        let _ = writeln!(
+            svg,
+            r#"<svg xmlns="http://www.w3.org/2000/svg" width="{0}" height="{0}" viewBox="0 0 {0} {0}">"#,
+            SVG_SIZE
+        );
+        if !self.transparent_export() {
+            let _ = writeln!(svg, r#"  <rect width="100%" height="100%" fill="{}"/>"#, hex(self.solid_export_background()));
+        }
+        if self.frame == Frame::Circle {
+            let _ = writeln!(
+                svg,
+                r#"  <circle cx="{c}" cy="{c}" r="{}" fill="none" stroke="{}" stroke-width="{}"/>"#,
+                CIRCLE_RADIUS,
+                hex(self.svg_color(GRAY)),
+                self.ring_width
+            );
+        } else {
+            let corners: Vec<String> = self.frame
+                .vertices()
+                .iter()
+                .map(|v| format!("{:.1},{:.1}", c + v.x, c + v.y))
+                .collect();
+            let _ = writeln!(
+                svg,
+                r#"  <polygon points="{}" fill="none" stroke="{}" stroke-width="{}"/>"#,
+                corners.join(" "),
+                hex(self.svg_color(GRAY)),
+                self.ring_width
+            );
+        }
+
+        // Decorative vowel points
+        for point in &self.ghost_points {
+            let _ = writeln!(
+                svg,
+                r#"  <circle cx="{:.1}" cy="{:.1}" r="{}" fill="{}" fill-opacity="{}"/>"#,
+                c + point.relative_pos.x,
+                c + point.relative_pos.y,
+                GHOST_RADIUS,
+                hex(self.svg_color(GHOST_COLOR)),
+                GHOST_COLOR.a
+            );
+        }
+
+        // Each segment is hidden behind a dash offset that animates to zero in turn
+        let duration = 1.0 / self.animation_speed;
+        for (i, line) in self.visible_segments(None).into_iter().enumerate() {
+            let length: f32 = line.windows(2).map(|pair| pair[0].distance(pair[1])).sum();
+            let points: Vec<String> = line.iter().map(|p| format!("{:.1},{:.1}", c + p.x, c + p.y)).collect();
+            let _ = writeln!(
+                svg,
+                concat!(
+                    r#"  <polyline points="{}" fill="none" stroke="{}" stroke-width="{}" "#,
+                    r#"stroke-dasharray="{len:.1}" stroke-dashoffset="{len:.1}">"#,
+                    "\n",
+                    r#"    <animate attributeName="stroke-dashoffset" from="{len:.1}" to="0" begin="{:.3}s" dur="{:.3}s" fill="freeze"/>"#,
+                    "\n  </polyline>"
+                ),
+                points.join(" "),
+                hex(self.svg_color(self.segment_color(i))),
+                self.line_width,
+                i as f32 * duration,
+                duration,
+                len = length
+            );
+        }
+
+        // Nodes and their numbers
+        let emphasized = self.emphasized_number();
+        for (i, point) in self.points.iter().enumerate() {
+            let color = self.svg_color(self.node_color(i));
+            let (x, y) = (c + point.relative_pos.x, c + point.relative_pos.y);
+            let radius = self.node_radius(point, emphasized);
+            let ring = if emphasized == Some(point.number) {
+                format!(r#" stroke="{}" stroke-width="2""#, hex(self.svg_color(GOLD)))
+            } else {
+                String::new()
+            };
+            let _ = writeln!(svg, r#"  <circle cx="{x:.1}" cy="{y:.1}" r="{radius}" fill="{}"{ring}/>"#, hex(color));
+            // Monochrome numbers are cut out of their filled node in the background color
+            let digit = match self.export_style {
+                ExportStyle::Color => self.ink(BLACK),
+                _ => self.solid_export_background(),
+            };
+            let _ = writeln!(
+                svg,
+                r#"  <text x="{x:.1}" y="{y:.1}" font-family="sans-serif" font-size="14" fill="{}" text-anchor="middle" dominant-baseline="central">{}</text>"#,
+                hex(digit),
+                point.number
+            );
+        }
+        svg.push_str("</svg>\n");
+        svg
+    }
+
+    /// Save the sigil as an SVG that animates its own drawing, returning its path
+    pub(crate) fn save_sigil_svg(&self) -> std::io::Result<String> {
+        let path = self.output_filename_as("svg")?;
+        std::fs::write(&path, self.animated_svg())?;
+        Ok(path.display().to_string())
+    }
+}
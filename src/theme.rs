@@ -0,0 +1,99 @@
+use crate::config::parse_color;
+use crate::BACKGROUND_COLOR;
+use macroquad::color::{Color, GREEN, ORANGE, RED, SKYBLUE};
+use serde::Deserialize;
+
+// Name of the built-in palette, always available first in the list
+pub const DEFAULT_THEME: &str = "default";
+
+/// The colors a sigil is drawn with
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Theme {
+    pub background: Color, // Background of the window and export
+    pub line: Color,       // Path segments
+    pub node: Color,       // Nodes between the first and last
+    pub start: Color,      // The first node
+    pub end: Color,        // The last node
+}
+
+impl Default for Theme {
+    fn default() -> Self {
+        Self {
+            background: BACKGROUND_COLOR,
+            line: SKYBLUE,
+            node: ORANGE,
+            start: GREEN,
+            end: RED,
+        }
+    }
+}
+
+/// Get the palettes that come with the app, the default first
+pub fn presets() -> Vec<(String, Theme)> {
+    let hex = |text: &str| parse_color(text).expect("built-in theme colors are valid");
+    vec![
+        (DEFAULT_THEME.to_string(), Theme::default()),
+        (
+            "mystic".to_string(),
+            Theme {
+                background: hex("#120a24"),
+                line: hex("#b48cff"),
+                node: hex("#e0c36a"),
+                start: hex("#7fffd4"),
+                end: hex("#ff6ec7"),
+            },
+        ),
+        (
+            "blood".to_string(),
+            Theme {
+                background: hex("#140404"),
+                line: hex("#c0101a"),
+                node: hex("#e8d8c8"),
+                start: hex("#ffb347"),
+                end: hex("#ff2a2a"),
+            },
+        ),
+        (
+            "mono".to_string(),
+            Theme {
+                background: hex("#101010"),
+                line: hex("#e0e0e0"),
+                node: hex("#a0a0a0"),
+                start: hex("#ffffff"),
+                end: hex("#606060"),
+            },
+        ),
+    ]
+}
+
+/// A named palette as written in the config file. Colors left out keep the default theme's.
+#[derive(Debug, Deserialize)]
+pub struct ThemeColors {
+    pub background: Option<String>, // Background color as "#RRGGBB"
+    pub line: Option<String>,       // Line color as "#RRGGBB" or "#RRGGBBAA"
+    pub node: Option<String>,       // Node color as "#RRGGBB" or "#RRGGBBAA"
+    pub start: Option<String>,      // First node color as "#RRGGBB" or "#RRGGBBAA"
+    pub end: Option<String>,        // Last node color as "#RRGGBB" or "#RRGGBBAA"
+}
+
+impl ThemeColors {
+    /// Parse the palette's colors, naming the first one that isn't valid
+    pub fn resolve(&self) -> Result<Theme, String> {
+        let mut theme = Theme::default();
+        let fields = [
+            ("background", &self.background, &mut theme.background),
+            ("line", &self.line, &mut theme.line),
+            ("node", &self.node, &mut theme.node),
+            ("start", &self.start, &mut theme.start),
+            ("end", &self.end, &mut theme.end),
+        ];
+        for (name, text, color) in fields {
+            if let Some(text) = text {
+                *color = parse_color(text).map_err(|e| format!("{}: {}", name, e))?;
+            }
+        }
+        // Exports have no alpha channel, so the background is always opaque
+        theme.background.a = 1.0;
+        Ok(theme)
+    }
+}